@@ -0,0 +1,137 @@
+use anchor_lang::prelude::*;
+use anchor_lang::solana_program::program_option::COption;
+use anchor_spl::token::Mint;
+use mpl_token_metadata::pda::{find_master_edition_account, find_metadata_account};
+use mpl_token_metadata::state::Key as MetadataKey;
+
+use crate::error::MplCpiError;
+use crate::reader::MetadataReader;
+
+/// Asserts that `metadata` is the metadata PDA of `mint` and is owned by the metadata program.
+pub fn assert_metadata_for_mint(metadata: &AccountInfo, mint: &Pubkey) -> Result<()> {
+    let (expected, _) = find_metadata_account(mint);
+    if metadata.key() != expected || *metadata.owner != mpl_token_metadata::ID {
+        return err!(MplCpiError::InvalidMetadataAccount);
+    }
+    Ok(())
+}
+
+/// Asserts that the NFT minted from `mint` is a verified member of the collection `collection_mint`.
+pub fn assert_collection_member(
+    metadata: &AccountInfo,
+    mint: &Pubkey,
+    collection_mint: &Pubkey,
+) -> Result<()> {
+    assert_metadata_for_mint(metadata, mint)?;
+    let data = metadata.try_borrow_data()?;
+    let collection = match MetadataReader::new(&data)?.collection()? {
+        Some(collection) => collection,
+        None => return err!(MplCpiError::CollectionMismatch),
+    };
+    if collection.key != *collection_mint {
+        return err!(MplCpiError::CollectionMismatch);
+    }
+    if !collection.verified {
+        return err!(MplCpiError::CollectionNotVerified);
+    }
+    Ok(())
+}
+
+/// [`assert_collection_member`] as a `bool`, for use in Anchor `constraint = ...` expressions.
+pub fn is_collection_member(
+    metadata: &AccountInfo,
+    mint: &Pubkey,
+    collection_mint: &Pubkey,
+) -> bool {
+    assert_collection_member(metadata, mint, collection_mint).is_ok()
+}
+
+/// Asserts that `creator` is a verified creator of the NFT minted from `mint`.
+///
+/// With `index` set, the creator must also sit at that position in the creators array,
+/// e.g. `Some(0)` for a Candy Machine creator PDA.
+pub fn assert_verified_creator(
+    metadata: &AccountInfo,
+    mint: &Pubkey,
+    creator: &Pubkey,
+    index: Option<usize>,
+) -> Result<()> {
+    assert_metadata_for_mint(metadata, mint)?;
+    let data = metadata.try_borrow_data()?;
+    let creators = MetadataReader::new(&data)?.creators()?;
+    let found = match index {
+        Some(index) => creators.get(index).filter(|c| c.address == *creator),
+        None => creators.iter().find(|c| c.address == *creator),
+    };
+    match found {
+        Some(found) if found.verified => Ok(()),
+        Some(_) => err!(MplCpiError::CreatorNotVerified),
+        None => err!(MplCpiError::CreatorNotFound),
+    }
+}
+
+/// [`assert_verified_creator`] as a `bool`, for use in Anchor `constraint = ...` expressions.
+pub fn is_verified_creator(
+    metadata: &AccountInfo,
+    mint: &Pubkey,
+    creator: &Pubkey,
+    index: Option<usize>,
+) -> bool {
+    assert_verified_creator(metadata, mint, creator, index).is_ok()
+}
+
+/// Asserts that `mint` is a genuine 1/1 NFT: zero decimals, a supply of one, the master edition
+/// PDA as mint authority, and valid metadata and master edition accounts.
+pub fn assert_is_nft(
+    mint: &Account<'_, Mint>,
+    metadata: &AccountInfo,
+    master_edition: &AccountInfo,
+) -> Result<()> {
+    assert_edition_backed(mint, metadata, master_edition)?;
+    match master_edition.try_borrow_data()?.first() {
+        Some(key)
+            if *key == MetadataKey::MasterEditionV2 as u8
+                || *key == MetadataKey::MasterEditionV1 as u8 =>
+        {
+            Ok(())
+        }
+        _ => err!(MplCpiError::InvalidEditionAccount),
+    }
+}
+
+/// Asserts that `mint` is a print edition of some master edition, with the same mint checks as
+/// [`assert_is_nft`].
+pub fn assert_is_edition(
+    mint: &Account<'_, Mint>,
+    metadata: &AccountInfo,
+    edition: &AccountInfo,
+) -> Result<()> {
+    assert_edition_backed(mint, metadata, edition)?;
+    match edition.try_borrow_data()?.first() {
+        Some(key) if *key == MetadataKey::EditionV1 as u8 => Ok(()),
+        _ => err!(MplCpiError::InvalidEditionAccount),
+    }
+}
+
+fn assert_edition_backed(
+    mint: &Account<'_, Mint>,
+    metadata: &AccountInfo,
+    edition: &AccountInfo,
+) -> Result<()> {
+    if mint.decimals != 0 {
+        return err!(MplCpiError::InvalidMintDecimals);
+    }
+    if mint.supply != 1 {
+        return err!(MplCpiError::InvalidMintSupply);
+    }
+    assert_metadata_for_mint(metadata, &mint.key())?;
+    // Master and print editions share the same PDA, which takes over the mint authority.
+    let (expected, _) = find_master_edition_account(&mint.key());
+    if edition.key() != expected || *edition.owner != mpl_token_metadata::ID {
+        return err!(MplCpiError::InvalidEditionAccount);
+    }
+    if mint.mint_authority != COption::Some(expected) {
+        return err!(MplCpiError::MintAuthorityNotEdition);
+    }
+    Ok(())
+}
@@ -0,0 +1,191 @@
+use anchor_lang::prelude::*;
+use anchor_spl::token::Token;
+
+use crate::error::MplCpiError;
+use crate::reader::MetadataReader;
+use crate::types::DataV2;
+use crate::{
+    FreezeDelegatedAccount, SignMetadata, UpdateMetadataAccountsV2, VerifyCollection,
+    VerifySizedCollectionItem,
+};
+
+/// Splits `accounts` into groups of `group_size`, failing if any accounts are left over.
+///
+/// The batch functions below issue one CPI per group, so callers should size batches to fit
+/// the compute budget of the transaction.
+pub fn account_groups<'s, 'info>(
+    accounts: &'s [AccountInfo<'info>],
+    group_size: usize,
+) -> Result<std::slice::ChunksExact<'s, AccountInfo<'info>>> {
+    let groups = accounts.chunks_exact(group_size);
+    if accounts.is_empty() || !groups.remainder().is_empty() {
+        return err!(MplCpiError::InvalidRemainingAccounts);
+    }
+    Ok(groups)
+}
+
+/// Verifies every metadata account in `ctx.remaining_accounts` into the collection.
+///
+/// Uses `verify_sized_collection_item` when the collection metadata has `collection_details`
+/// and `verify_collection` otherwise, as the metadata program only accepts the matching one.
+pub fn verify_collection_batch<'a, 'b, 'c, 'info>(
+    ctx: CpiContext<'a, 'b, 'c, 'info, VerifyCollectionBatch<'info>>,
+) -> Result<()> {
+    let groups = account_groups(&ctx.remaining_accounts, 1)?;
+    let sized = {
+        let data = ctx.accounts.collection.try_borrow_data()?;
+        MetadataReader::new(&data)?.collection_details()?.is_some()
+    };
+    for group in groups {
+        if sized {
+            crate::verify_sized_collection_item(CpiContext::new_with_signer(
+                ctx.program.clone(),
+                VerifySizedCollectionItem {
+                    metadata: group[0].clone(),
+                    collection_authority: ctx.accounts.collection_authority.clone(),
+                    payer: ctx.accounts.payer.clone(),
+                    collection_mint: ctx.accounts.collection_mint.clone(),
+                    collection: ctx.accounts.collection.clone(),
+                    collection_master_edition_account: ctx
+                        .accounts
+                        .collection_master_edition_account
+                        .clone(),
+                    collection_authority_record: ctx.accounts.collection_authority_record.clone(),
+                },
+                ctx.signer_seeds,
+            ))?;
+        } else {
+            crate::verify_collection(CpiContext::new_with_signer(
+                ctx.program.clone(),
+                VerifyCollection {
+                    metadata: group[0].clone(),
+                    collection_authority: ctx.accounts.collection_authority.clone(),
+                    payer: ctx.accounts.payer.clone(),
+                    collection_mint: ctx.accounts.collection_mint.clone(),
+                    collection: ctx.accounts.collection.clone(),
+                    collection_master_edition_account: ctx
+                        .accounts
+                        .collection_master_edition_account
+                        .clone(),
+                    collection_authority_record: ctx.accounts.collection_authority_record.clone(),
+                },
+                ctx.signer_seeds,
+            ))?;
+        }
+    }
+    Ok(())
+}
+
+#[derive(Accounts)]
+pub struct VerifyCollectionBatch<'info> {
+    /// CHECK: update authority or approved delegate of the collection
+    #[account(mut, signer)]
+    pub collection_authority: AccountInfo<'info>,
+    /// CHECK: pays for any accounts created
+    #[account(mut, signer)]
+    pub payer: AccountInfo<'info>,
+    /// CHECK: mint of the collection NFT
+    pub collection_mint: AccountInfo<'info>,
+    /// CHECK: metadata PDA of the collection NFT, whose size is updated for sized collections
+    #[account(mut)]
+    pub collection: AccountInfo<'info>,
+    /// CHECK: master edition PDA of the collection NFT
+    pub collection_master_edition_account: AccountInfo<'info>,
+    /// CHECK: collection authority record PDA of the delegate
+    pub collection_authority_record: AccountInfo<'info>,
+}
+
+/// Updates every metadata account in `ctx.remaining_accounts` with the matching entry of `data`.
+pub fn update_metadata_accounts_v2_batch<'a, 'b, 'c, 'info>(
+    ctx: CpiContext<'a, 'b, 'c, 'info, UpdateMetadataAccountsV2Batch<'info>>,
+    data: Vec<DataV2>,
+    primary_sale_happened: bool,
+    is_mutable: bool,
+) -> Result<()> {
+    let groups = account_groups(&ctx.remaining_accounts, 1)?;
+    if groups.len() != data.len() {
+        return err!(MplCpiError::InvalidRemainingAccounts);
+    }
+    for (group, data) in groups.zip(data) {
+        crate::update_metadata_accounts_v2(
+            CpiContext::new_with_signer(
+                ctx.program.clone(),
+                UpdateMetadataAccountsV2 {
+                    metadata_account: group[0].clone(),
+                    update_authority: ctx.accounts.update_authority.clone(),
+                    new_update_authority: ctx.accounts.new_update_authority.clone(),
+                },
+                ctx.signer_seeds,
+            ),
+            data.into(),
+            primary_sale_happened,
+            is_mutable,
+        )?;
+    }
+    Ok(())
+}
+
+#[derive(Accounts)]
+pub struct UpdateMetadataAccountsV2Batch<'info> {
+    /// CHECK: update authority of the metadata
+    #[account(signer)]
+    pub update_authority: AccountInfo<'info>,
+    /// CHECK: update authority to set on the metadata
+    pub new_update_authority: AccountInfo<'info>,
+}
+
+/// Signs every metadata account in `ctx.remaining_accounts` as `creator`.
+pub fn sign_metadata_accounts_batch<'a, 'b, 'c, 'info>(
+    ctx: CpiContext<'a, 'b, 'c, 'info, SignMetadataAccountsBatch<'info>>,
+) -> Result<()> {
+    for group in account_groups(&ctx.remaining_accounts, 1)? {
+        crate::sign_metadata(CpiContext::new_with_signer(
+            ctx.program.clone(),
+            SignMetadata {
+                metadata: group[0].clone(),
+                creator: ctx.accounts.creator.clone(),
+            },
+            ctx.signer_seeds,
+        ))?;
+    }
+    Ok(())
+}
+
+#[derive(Accounts)]
+pub struct SignMetadataAccountsBatch<'info> {
+    /// CHECK: creator listed on the metadata
+    #[account(signer)]
+    pub creator: AccountInfo<'info>,
+}
+
+/// Freezes delegated token accounts given as `[token_account, edition, mint]` groups in
+/// `ctx.remaining_accounts`.
+pub fn freeze_delegated_account_batch<'a, 'b, 'c, 'info>(
+    ctx: CpiContext<'a, 'b, 'c, 'info, FreezeDelegatedAccountBatch<'info>>,
+) -> Result<()> {
+    for group in account_groups(&ctx.remaining_accounts, 3)? {
+        crate::freeze_delegated_account(CpiContext::new_with_signer(
+            ctx.program.clone(),
+            FreezeDelegatedAccount {
+                delegate: ctx.accounts.delegate.clone(),
+                token_account: group[0].clone(),
+                edition: group[1].clone(),
+                update_authority: ctx.accounts.update_authority.clone(),
+                mint: group[2].clone(),
+                token_program: ctx.accounts.token_program.clone(),
+            },
+            ctx.signer_seeds,
+        ))?;
+    }
+    Ok(())
+}
+
+#[derive(Accounts)]
+pub struct FreezeDelegatedAccountBatch<'info> {
+    /// CHECK: delegate of the token account
+    #[account(mut, signer)]
+    pub delegate: AccountInfo<'info>,
+    /// CHECK: unused by the metadata program
+    pub update_authority: AccountInfo<'info>,
+    pub token_program: Program<'info, Token>,
+}
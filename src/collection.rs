@@ -0,0 +1,363 @@
+use anchor_lang::prelude::*;
+use anchor_spl::associated_token::AssociatedToken;
+use anchor_spl::token::Token;
+use mpl_token_metadata::pda::find_collection_authority_account;
+use mpl_token_metadata::state::{Collection, CollectionDetails, Key as MetadataKey};
+
+use crate::error::MplCpiError;
+use crate::mint::{create_nft_mint, NftMint};
+use crate::reader::MetadataReader;
+use crate::types::Creator;
+use crate::{
+    ApproveCollectionAuthority, CreateMasterEditionV3, CreateMetadataAccountV3,
+    RevokeCollectionAuthority, UnverifyCollection, VerifyCollection, VerifySizedCollectionItem,
+};
+
+/// Collection authority record PDA of `delegate` for the collection minted from `collection_mint`.
+pub fn find_collection_authority_record(
+    collection_mint: &Pubkey,
+    delegate: &Pubkey,
+) -> (Pubkey, u8) {
+    find_collection_authority_account(collection_mint, delegate)
+}
+
+/// Returns whether `delegate` currently holds an approved collection authority record.
+pub fn has_collection_authority(
+    collection_authority_record: &AccountInfo,
+    collection_mint: &Pubkey,
+    delegate: &Pubkey,
+) -> Result<bool> {
+    let (expected, _) = find_collection_authority_record(collection_mint, delegate);
+    if collection_authority_record.key() != expected {
+        return err!(MplCpiError::InvalidCollectionAuthorityRecord);
+    }
+    if *collection_authority_record.owner != mpl_token_metadata::ID {
+        return Ok(false);
+    }
+    let data = collection_authority_record.try_borrow_data()?;
+    Ok(data.first() == Some(&(MetadataKey::CollectionAuthorityRecord as u8)))
+}
+
+/// Approves `new_collection_authority` as a collection delegate, doing nothing if it already is.
+pub fn approve_collection_delegate<'a, 'b, 'c, 'info>(
+    ctx: CpiContext<'a, 'b, 'c, 'info, ApproveCollectionAuthority<'info>>,
+) -> Result<()> {
+    if has_collection_authority(
+        &ctx.accounts.collection_authority_record,
+        &ctx.accounts.mint.key(),
+        &ctx.accounts.new_collection_authority.key(),
+    )? {
+        return Ok(());
+    }
+    crate::approve_collection_authority(ctx)
+}
+
+/// Revokes the collection delegate `delegate_authority`, doing nothing if it holds no record.
+pub fn revoke_collection_delegate<'a, 'b, 'c, 'info>(
+    ctx: CpiContext<'a, 'b, 'c, 'info, RevokeCollectionAuthority<'info>>,
+) -> Result<()> {
+    if !has_collection_authority(
+        &ctx.accounts.collection_authority_record,
+        &ctx.accounts.mint.key(),
+        &ctx.accounts.delegate_authority.key(),
+    )? {
+        return Ok(());
+    }
+    crate::revoke_collection_authority(ctx)
+}
+
+/// Verifies an item into a collection as an approved collection delegate.
+///
+/// Checks that `collection_authority_record` is the record derived for `collection_authority`
+/// and has been approved before handing it to the metadata program.
+pub fn verify_collection_as_delegate<'a, 'b, 'c, 'info>(
+    ctx: CpiContext<'a, 'b, 'c, 'info, VerifyCollection<'info>>,
+) -> Result<()> {
+    if !has_collection_authority(
+        &ctx.accounts.collection_authority_record,
+        &ctx.accounts.collection_mint.key(),
+        &ctx.accounts.collection_authority.key(),
+    )? {
+        return err!(MplCpiError::CollectionAuthorityRecordMissing);
+    }
+    crate::verify_collection(ctx)
+}
+
+/// Unverifies an item from a collection as an approved collection delegate.
+pub fn unverify_collection_as_delegate<'a, 'b, 'c, 'info>(
+    ctx: CpiContext<'a, 'b, 'c, 'info, UnverifyCollection<'info>>,
+) -> Result<()> {
+    if !has_collection_authority(
+        &ctx.accounts.collection_authority_record,
+        &ctx.accounts.collection_mint.key(),
+        &ctx.accounts.collection_authority.key(),
+    )? {
+        return err!(MplCpiError::CollectionAuthorityRecordMissing);
+    }
+    crate::unverify_collection(ctx)
+}
+
+/// Creates a sized collection parent NFT in one go.
+///
+/// Creates the mint, the associated token account of `token_account_owner` holding the single
+/// token, metadata with `CollectionDetails::V1 { size: 0 }` and a master edition with a max
+/// supply of zero.
+pub fn create_collection_nft<'a, 'b, 'c, 'info>(
+    ctx: CpiContext<'a, 'b, 'c, 'info, CreateCollectionNft<'info>>,
+    name: String,
+    symbol: String,
+    uri: String,
+    seller_fee_basis_points: u16,
+    creators: Option<Vec<Creator>>,
+    is_mutable: bool,
+) -> Result<()> {
+    let accounts = ctx.accounts;
+    create_nft_mint(
+        NftMint {
+            payer: accounts.payer.clone(),
+            mint: accounts.mint.clone(),
+            mint_authority: accounts.mint_authority.clone(),
+            token_account: accounts.token_account,
+            token_account_owner: accounts.token_account_owner,
+            associated_token_program: accounts.associated_token_program.to_account_info(),
+            system_program: accounts.system_program.to_account_info(),
+            token_program: accounts.token_program.to_account_info(),
+            rent: accounts.rent.to_account_info(),
+        },
+        ctx.signer_seeds,
+    )?;
+
+    crate::create_metadata_accounts_v3(
+        CpiContext::new_with_signer(
+            ctx.program.clone(),
+            CreateMetadataAccountV3 {
+                metadata_account: accounts.metadata.clone(),
+                mint: accounts.mint.clone(),
+                mint_authority: accounts.mint_authority.clone(),
+                payer: accounts.payer.clone(),
+                update_authority: accounts.update_authority.clone(),
+                system_program: accounts.system_program.clone(),
+                rent: accounts.rent.clone(),
+            },
+            ctx.signer_seeds,
+        ),
+        name,
+        symbol,
+        uri,
+        seller_fee_basis_points,
+        true,
+        is_mutable,
+        None,
+        None,
+        creators.map(|creators| creators.into_iter().map(Into::into).collect()),
+        Some(CollectionDetails::V1 { size: 0 }),
+    )?;
+
+    crate::create_master_edition_v3(
+        CpiContext::new_with_signer(
+            ctx.program,
+            CreateMasterEditionV3 {
+                edition: accounts.master_edition,
+                mint: accounts.mint,
+                update_authority: accounts.update_authority,
+                mint_authority: accounts.mint_authority,
+                metadata: accounts.metadata,
+                payer: accounts.payer,
+                system_program: accounts.system_program,
+                token_program: accounts.token_program,
+                rent: accounts.rent,
+            },
+            ctx.signer_seeds,
+        ),
+        Some(0),
+    )
+}
+
+#[derive(Accounts)]
+pub struct CreateCollectionNft<'info> {
+    /// CHECK: metadata PDA of the collection mint, created by the metadata program
+    #[account(mut)]
+    pub metadata: AccountInfo<'info>,
+    /// CHECK: master edition PDA of the collection mint, created by the metadata program
+    #[account(mut)]
+    pub master_edition: AccountInfo<'info>,
+    /// CHECK: collection mint, created here; signs
+    #[account(mut)]
+    pub mint: AccountInfo<'info>,
+    /// CHECK: mint authority of the new mint, handed to the master edition; signs
+    pub mint_authority: AccountInfo<'info>,
+    /// CHECK: update authority of the collection; signs
+    pub update_authority: AccountInfo<'info>,
+    /// CHECK: associated token account created for the collection token
+    #[account(mut)]
+    pub token_account: AccountInfo<'info>,
+    /// CHECK: owner of the collection token account
+    pub token_account_owner: AccountInfo<'info>,
+    /// CHECK: pays for every account created; signs
+    #[account(mut)]
+    pub payer: AccountInfo<'info>,
+    pub associated_token_program: Program<'info, AssociatedToken>,
+    pub system_program: Program<'info, System>,
+    pub token_program: Program<'info, Token>,
+    pub rent: Sysvar<'info, Rent>,
+}
+
+/// Mints a new NFT straight into a collection and verifies it.
+///
+/// Creates the mint, the associated token account of `token_account_owner` holding the single
+/// token, metadata pointing at `collection_mint` and a master edition, then verifies the item
+/// with `verify_sized_collection_item` when the collection metadata has `collection_details`
+/// and with `verify_collection` otherwise. `collection_authority` may be the collection's update
+/// authority or an approved delegate; `collection_authority_record` is only passed on once it
+/// has been approved.
+pub fn mint_nft_into_collection<'a, 'b, 'c, 'info>(
+    ctx: CpiContext<'a, 'b, 'c, 'info, MintNftIntoCollection<'info>>,
+    name: String,
+    symbol: String,
+    uri: String,
+    seller_fee_basis_points: u16,
+    creators: Option<Vec<Creator>>,
+    is_mutable: bool,
+    max_supply: Option<u64>,
+) -> Result<()> {
+    let accounts = ctx.accounts;
+    let sized = {
+        let data = accounts.collection_metadata.try_borrow_data()?;
+        MetadataReader::new(&data)?.collection_details()?.is_some()
+    };
+
+    create_nft_mint(
+        NftMint {
+            payer: accounts.payer.clone(),
+            mint: accounts.mint.clone(),
+            mint_authority: accounts.mint_authority.clone(),
+            token_account: accounts.token_account,
+            token_account_owner: accounts.token_account_owner,
+            associated_token_program: accounts.associated_token_program.to_account_info(),
+            system_program: accounts.system_program.to_account_info(),
+            token_program: accounts.token_program.to_account_info(),
+            rent: accounts.rent.to_account_info(),
+        },
+        ctx.signer_seeds,
+    )?;
+
+    crate::create_metadata_accounts_v3(
+        CpiContext::new_with_signer(
+            ctx.program.clone(),
+            CreateMetadataAccountV3 {
+                metadata_account: accounts.metadata.clone(),
+                mint: accounts.mint.clone(),
+                mint_authority: accounts.mint_authority.clone(),
+                payer: accounts.payer.clone(),
+                update_authority: accounts.update_authority.clone(),
+                system_program: accounts.system_program.clone(),
+                rent: accounts.rent.clone(),
+            },
+            ctx.signer_seeds,
+        ),
+        name,
+        symbol,
+        uri,
+        seller_fee_basis_points,
+        true,
+        is_mutable,
+        None,
+        Some(Collection {
+            verified: false,
+            key: accounts.collection_mint.key(),
+        }),
+        creators.map(|creators| creators.into_iter().map(Into::into).collect()),
+        None,
+    )?;
+
+    crate::create_master_edition_v3(
+        CpiContext::new_with_signer(
+            ctx.program.clone(),
+            CreateMasterEditionV3 {
+                edition: accounts.master_edition,
+                mint: accounts.mint,
+                update_authority: accounts.update_authority,
+                mint_authority: accounts.mint_authority,
+                metadata: accounts.metadata.clone(),
+                payer: accounts.payer.clone(),
+                system_program: accounts.system_program,
+                token_program: accounts.token_program,
+                rent: accounts.rent,
+            },
+            ctx.signer_seeds,
+        ),
+        max_supply,
+    )?;
+
+    if sized {
+        crate::verify_sized_collection_item(CpiContext::new_with_signer(
+            ctx.program,
+            VerifySizedCollectionItem {
+                metadata: accounts.metadata,
+                collection_authority: accounts.collection_authority,
+                payer: accounts.payer,
+                collection_mint: accounts.collection_mint,
+                collection: accounts.collection_metadata,
+                collection_master_edition_account: accounts.collection_master_edition,
+                collection_authority_record: accounts.collection_authority_record,
+            },
+            ctx.signer_seeds,
+        ))
+    } else {
+        crate::verify_collection(CpiContext::new_with_signer(
+            ctx.program,
+            VerifyCollection {
+                metadata: accounts.metadata,
+                collection_authority: accounts.collection_authority,
+                payer: accounts.payer,
+                collection_mint: accounts.collection_mint,
+                collection: accounts.collection_metadata,
+                collection_master_edition_account: accounts.collection_master_edition,
+                collection_authority_record: accounts.collection_authority_record,
+            },
+            ctx.signer_seeds,
+        ))
+    }
+}
+
+#[derive(Accounts)]
+pub struct MintNftIntoCollection<'info> {
+    /// CHECK: metadata PDA of the new mint, created by the metadata program
+    #[account(mut)]
+    pub metadata: AccountInfo<'info>,
+    /// CHECK: master edition PDA of the new mint, created by the metadata program
+    #[account(mut)]
+    pub master_edition: AccountInfo<'info>,
+    /// CHECK: new NFT mint, created here; signs
+    #[account(mut)]
+    pub mint: AccountInfo<'info>,
+    /// CHECK: mint authority of the new mint, handed to the master edition; signs
+    pub mint_authority: AccountInfo<'info>,
+    /// CHECK: update authority of the new NFT; signs
+    pub update_authority: AccountInfo<'info>,
+    /// CHECK: associated token account created for the new NFT
+    #[account(mut)]
+    pub token_account: AccountInfo<'info>,
+    /// CHECK: owner of the new NFT
+    pub token_account_owner: AccountInfo<'info>,
+    /// CHECK: pays for every account created; signs
+    #[account(mut)]
+    pub payer: AccountInfo<'info>,
+    /// CHECK: update authority or approved delegate of the collection; signs. Writable because
+    /// `verify_collection`, used for unsized collections, takes it as writable
+    #[account(mut)]
+    pub collection_authority: AccountInfo<'info>,
+    /// CHECK: mint of the collection NFT
+    pub collection_mint: AccountInfo<'info>,
+    /// CHECK: metadata PDA of the collection NFT, validated by the metadata program
+    #[account(mut)]
+    pub collection_metadata: AccountInfo<'info>,
+    /// CHECK: master edition PDA of the collection NFT
+    pub collection_master_edition: AccountInfo<'info>,
+    /// CHECK: collection authority record PDA of `collection_authority`, if it is a delegate
+    pub collection_authority_record: AccountInfo<'info>,
+    pub associated_token_program: Program<'info, AssociatedToken>,
+    pub system_program: Program<'info, System>,
+    pub token_program: Program<'info, Token>,
+    pub rent: Sysvar<'info, Rent>,
+}
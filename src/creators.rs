@@ -0,0 +1,93 @@
+use anchor_lang::prelude::*;
+use mpl_token_metadata::state::{Metadata, TokenMetadataAccount};
+
+use crate::error::MplCpiError;
+use crate::{RemoveCreatorVerification, SignMetadata};
+
+/// A creator account together with the seeds it signs with.
+///
+/// Use empty `signer_seeds` for creators that already signed the transaction.
+pub struct CreatorSigner<'a, 'info> {
+    pub creator: AccountInfo<'info>,
+    pub signer_seeds: &'a [&'a [u8]],
+}
+
+impl<'a, 'info> CreatorSigner<'a, 'info> {
+    fn seeds(&self) -> &[&'a [&'a [u8]]] {
+        if self.signer_seeds.is_empty() {
+            &[]
+        } else {
+            std::slice::from_ref(&self.signer_seeds)
+        }
+    }
+}
+
+/// Verifies every creator in `creators` on `metadata`, skipping creators already verified.
+///
+/// Returns the number of creators that were signed.
+pub fn sign_metadata_batch<'info>(
+    program: AccountInfo<'info>,
+    metadata: AccountInfo<'info>,
+    creators: &[CreatorSigner<'_, 'info>],
+) -> Result<usize> {
+    let pending = creators_with_status(&metadata, creators, false)?;
+    for signer in &pending {
+        crate::sign_metadata(CpiContext::new_with_signer(
+            program.clone(),
+            SignMetadata {
+                metadata: metadata.clone(),
+                creator: signer.creator.clone(),
+            },
+            signer.seeds(),
+        ))?;
+    }
+    Ok(pending.len())
+}
+
+/// Removes verification of every creator in `creators` from `metadata`, skipping creators
+/// that are not verified.
+///
+/// Returns the number of creators that were unverified.
+pub fn remove_creator_verification_batch<'info>(
+    program: AccountInfo<'info>,
+    metadata: AccountInfo<'info>,
+    creators: &[CreatorSigner<'_, 'info>],
+) -> Result<usize> {
+    let pending = creators_with_status(&metadata, creators, true)?;
+    for signer in &pending {
+        crate::remove_creator_verification(CpiContext::new_with_signer(
+            program.clone(),
+            RemoveCreatorVerification {
+                metadata: metadata.clone(),
+                creator: signer.creator.clone(),
+            },
+            signer.seeds(),
+        ))?;
+    }
+    Ok(pending.len())
+}
+
+fn creators_with_status<'s, 'a, 'info>(
+    metadata: &AccountInfo<'info>,
+    creators: &'s [CreatorSigner<'a, 'info>],
+    verified: bool,
+) -> Result<Vec<&'s CreatorSigner<'a, 'info>>> {
+    let listed = Metadata::from_account_info(metadata)?
+        .data
+        .creators
+        .unwrap_or_default();
+    let mut pending: Vec<&CreatorSigner> = Vec::with_capacity(creators.len());
+    for signer in creators {
+        let creator = listed
+            .iter()
+            .find(|creator| creator.address == signer.creator.key())
+            .ok_or(MplCpiError::CreatorNotFound)?;
+        let queued = pending
+            .iter()
+            .any(|queued| queued.creator.key == signer.creator.key);
+        if creator.verified == verified && !queued {
+            pending.push(signer);
+        }
+    }
+    Ok(pending)
+}
@@ -0,0 +1,347 @@
+use anchor_lang::prelude::*;
+use anchor_spl::associated_token::AssociatedToken;
+use anchor_spl::token::Token;
+use mpl_token_metadata::pda::{find_edition_account, find_master_edition_account};
+use mpl_token_metadata::state::{EditionMarker, MasterEditionV2, TokenMetadataAccount};
+
+use crate::error::MplCpiError;
+use crate::mint::{create_nft_mint, NftMint};
+use crate::EDITION_MARKER_BIT_SIZE;
+
+/// Number of the edition marker account whose ledger tracks `edition`.
+pub fn edition_marker_number(edition: u64) -> u64 {
+    edition / EDITION_MARKER_BIT_SIZE
+}
+
+/// Edition marker PDA for `edition` of the master edition minted from `master_mint`.
+pub fn find_edition_marker_account(master_mint: &Pubkey, edition: u64) -> (Pubkey, u8) {
+    find_edition_account(master_mint, edition_marker_number(edition).to_string())
+}
+
+/// Byte index into `EditionMarker::ledger` and the bit mask for `edition` within that byte.
+pub fn edition_marker_bit(edition: u64) -> (usize, u8) {
+    let offset = (edition % EDITION_MARKER_BIT_SIZE) as usize;
+    (offset / 8, 1 << (7 - offset % 8))
+}
+
+/// Returns whether `edition` has already been printed according to its edition marker.
+///
+/// A marker account that has not been created yet means no edition it covers was minted.
+pub fn is_edition_minted(
+    edition_marker: &AccountInfo,
+    master_mint: &Pubkey,
+    edition: u64,
+) -> Result<bool> {
+    if edition == 0 {
+        return err!(MplCpiError::InvalidEditionNumber);
+    }
+    let (expected, _) = find_edition_marker_account(master_mint, edition);
+    if edition_marker.key() != expected {
+        return err!(MplCpiError::InvalidEditionMarker);
+    }
+    if edition_marker.data_is_empty() {
+        return Ok(false);
+    }
+    let marker = EditionMarker::from_account_info(edition_marker)?;
+    let (index, mask) = edition_marker_bit(edition);
+    Ok(marker.ledger[index] & mask != 0)
+}
+
+/// Finds the lowest unminted edition at or after `start_edition`, scanning at most
+/// `max_markers` edition markers.
+///
+/// `ledger_for_marker` is called with each marker number in turn and returns the marker's
+/// ledger, or `None` if the marker account does not exist yet. Off-chain callers can back it
+/// with RPC lookups; on-chain callers should use [`find_next_edition`].
+pub fn next_free_edition<F>(
+    master_edition: &MasterEditionV2,
+    start_edition: u64,
+    max_markers: u64,
+    mut ledger_for_marker: F,
+) -> Result<u64>
+where
+    F: FnMut(u64) -> Result<Option<[u8; 31]>>,
+{
+    if let Some(max_supply) = master_edition.max_supply {
+        if master_edition.supply >= max_supply {
+            return err!(MplCpiError::MaxSupplyReached);
+        }
+    }
+    let last_edition = master_edition.max_supply.unwrap_or(u64::MAX);
+    let start_edition = start_edition.max(1);
+    let first_marker = edition_marker_number(start_edition);
+
+    for marker_number in first_marker..first_marker.saturating_add(max_markers) {
+        let ledger = ledger_for_marker(marker_number)?;
+        let from = match marker_number.checked_mul(EDITION_MARKER_BIT_SIZE) {
+            Some(first) => first.max(start_edition),
+            // Past the last representable edition number.
+            None => return err!(MplCpiError::MaxSupplyReached),
+        };
+        let to = marker_number
+            .checked_add(1)
+            .and_then(|next| next.checked_mul(EDITION_MARKER_BIT_SIZE))
+            .map_or(u64::MAX, |next_first| next_first - 1)
+            .min(last_edition);
+        for edition in from..=to {
+            let taken = match &ledger {
+                Some(ledger) => {
+                    let (index, mask) = edition_marker_bit(edition);
+                    ledger[index] & mask != 0
+                }
+                None => false,
+            };
+            if !taken {
+                return Ok(edition);
+            }
+        }
+        if to == last_edition {
+            return err!(MplCpiError::MaxSupplyReached);
+        }
+    }
+    err!(MplCpiError::EditionScanExhausted)
+}
+
+/// On-chain variant of [`next_free_edition`].
+///
+/// `edition_markers` must hold consecutive edition marker accounts starting with the marker
+/// that covers `start_edition`; the scan is bounded by the number of markers passed in.
+/// `master_edition` must be the master edition PDA of `master_mint`.
+pub fn find_next_edition(
+    master_edition: &AccountInfo,
+    master_mint: &Pubkey,
+    start_edition: u64,
+    edition_markers: &[AccountInfo],
+) -> Result<u64> {
+    let (expected, _) = find_master_edition_account(master_mint);
+    if master_edition.key() != expected || *master_edition.owner != mpl_token_metadata::ID {
+        return err!(MplCpiError::InvalidEditionAccount);
+    }
+    let master_edition = MasterEditionV2::from_account_info(master_edition)?;
+    let first_marker = edition_marker_number(start_edition.max(1));
+    next_free_edition(
+        &master_edition,
+        start_edition,
+        edition_markers.len() as u64,
+        |marker_number| {
+            let marker = &edition_markers[(marker_number - first_marker) as usize];
+            let (expected, _) =
+                find_edition_marker_account(master_mint, marker_number * EDITION_MARKER_BIT_SIZE);
+            if marker.key() != expected {
+                return err!(MplCpiError::InvalidEditionMarker);
+            }
+            if marker.data_is_empty() {
+                return Ok(None);
+            }
+            Ok(Some(EditionMarker::from_account_info(marker)?.ledger))
+        },
+    )
+}
+
+/// Prints `edition` of a master edition into a brand new mint.
+///
+/// Creates and initializes `new_mint`, creates the associated token account of
+/// `new_token_account_owner`, mints the single token into it and then prints the edition.
+pub fn print_edition<'a, 'b, 'c, 'info>(
+    ctx: CpiContext<'a, 'b, 'c, 'info, PrintEdition<'info>>,
+    edition: u64,
+) -> Result<()> {
+    let accounts = ctx.accounts;
+    create_nft_mint(
+        NftMint {
+            payer: accounts.payer.clone(),
+            mint: accounts.new_mint.clone(),
+            mint_authority: accounts.new_mint_authority.clone(),
+            token_account: accounts.new_token_account,
+            token_account_owner: accounts.new_token_account_owner,
+            associated_token_program: accounts.associated_token_program.to_account_info(),
+            system_program: accounts.system_program.to_account_info(),
+            token_program: accounts.token_program.to_account_info(),
+            rent: accounts.rent.to_account_info(),
+        },
+        ctx.signer_seeds,
+    )?;
+
+    crate::mint_new_edition_from_master_edition_via_token(
+        CpiContext::new_with_signer(
+            ctx.program,
+            crate::MintNewEditionFromMasterEditionViaToken {
+                new_metadata: accounts.new_metadata,
+                new_edition: accounts.new_edition,
+                master_edition: accounts.master_edition,
+                new_mint: accounts.new_mint,
+                edition_mark_pda: accounts.edition_mark_pda,
+                new_mint_authority: accounts.new_mint_authority,
+                payer: accounts.payer,
+                token_account_owner: accounts.token_account_owner,
+                token_account: accounts.token_account,
+                new_metadata_update_authority: accounts.new_metadata_update_authority,
+                metadata: accounts.metadata,
+                metadata_mint: accounts.metadata_mint,
+                system_program: accounts.system_program,
+                token_program: accounts.token_program,
+                rent: accounts.rent,
+            },
+            ctx.signer_seeds,
+        ),
+        edition,
+    )
+}
+
+#[derive(Accounts)]
+pub struct PrintEdition<'info> {
+    /// CHECK: metadata PDA of the new edition mint
+    #[account(mut)]
+    pub new_metadata: AccountInfo<'info>,
+    /// CHECK: edition PDA of the new edition mint
+    #[account(mut)]
+    pub new_edition: AccountInfo<'info>,
+    /// CHECK: master edition PDA, validated by the metadata program
+    #[account(mut)]
+    pub master_edition: AccountInfo<'info>,
+    /// CHECK: mint of the new edition; signs
+    #[account(mut)]
+    pub new_mint: AccountInfo<'info>,
+    /// CHECK: edition marker PDA, checked against the edition number before the CPI
+    #[account(mut)]
+    pub edition_mark_pda: AccountInfo<'info>,
+    /// CHECK: mint authority of the new edition mint; signs
+    pub new_mint_authority: AccountInfo<'info>,
+    /// CHECK: associated token account created for the new edition
+    #[account(mut)]
+    pub new_token_account: AccountInfo<'info>,
+    /// CHECK: owner of the new edition token account
+    pub new_token_account_owner: AccountInfo<'info>,
+    /// CHECK: pays for any accounts created; signs
+    #[account(mut)]
+    pub payer: AccountInfo<'info>,
+    /// CHECK: owner of the token account holding the master edition; signs
+    pub token_account_owner: AccountInfo<'info>,
+    /// CHECK: token account holding the master edition
+    pub token_account: AccountInfo<'info>,
+    /// CHECK: update authority of the master edition metadata
+    pub new_metadata_update_authority: AccountInfo<'info>,
+    /// CHECK: metadata PDA of the master edition
+    pub metadata: AccountInfo<'info>,
+    /// CHECK: mint of the master edition
+    pub metadata_mint: AccountInfo<'info>,
+    pub associated_token_program: Program<'info, AssociatedToken>,
+    pub system_program: Program<'info, System>,
+    pub token_program: Program<'info, Token>,
+    pub rent: Sysvar<'info, Rent>,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use mpl_token_metadata::state::Key;
+
+    fn master_edition(supply: u64, max_supply: Option<u64>) -> MasterEditionV2 {
+        MasterEditionV2 {
+            key: Key::MasterEditionV2,
+            supply,
+            max_supply,
+        }
+    }
+
+    fn ledger(editions: impl IntoIterator<Item = u64>) -> [u8; 31] {
+        let mut ledger = [0; 31];
+        for edition in editions {
+            let (index, mask) = edition_marker_bit(edition);
+            ledger[index] |= mask;
+        }
+        ledger
+    }
+
+    fn assert_error(result: Result<u64>, expected: MplCpiError) {
+        assert_eq!(
+            ProgramError::from(result.unwrap_err()),
+            ProgramError::from(error!(expected))
+        );
+    }
+
+    #[test]
+    fn next_free_edition_crosses_marker_boundary() {
+        let master_edition = master_edition(247, None);
+        let markers = |marker_number| match marker_number {
+            0 => Ok(Some(ledger(1..=247))),
+            _ => Ok(None),
+        };
+        assert_eq!(
+            next_free_edition(&master_edition, 1, 2, markers).unwrap(),
+            248
+        );
+        assert_eq!(
+            next_free_edition(&master_edition, 247, 2, markers).unwrap(),
+            248
+        );
+    }
+
+    #[test]
+    fn next_free_edition_skips_full_ledgers() {
+        let master_edition = master_edition(500, None);
+        let markers = |marker_number| match marker_number {
+            0 => Ok(Some(ledger(1..=247))),
+            1 => Ok(Some([0xff; 31])),
+            _ => Ok(Some(ledger([496, 497]))),
+        };
+        assert_eq!(
+            next_free_edition(&master_edition, 1, 3, markers).unwrap(),
+            498
+        );
+    }
+
+    #[test]
+    fn next_free_edition_fills_gaps_below_max_supply() {
+        let master_edition = master_edition(4, Some(10));
+        let markers = |_| Ok(Some(ledger([1, 2, 4, 5, 6])));
+        assert_eq!(
+            next_free_edition(&master_edition, 1, 1, markers).unwrap(),
+            3
+        );
+        assert_eq!(
+            next_free_edition(&master_edition, 4, 1, markers).unwrap(),
+            7
+        );
+    }
+
+    #[test]
+    fn next_free_edition_stops_at_max_supply() {
+        let markers = |_| Ok(Some(ledger(1..=9)));
+        assert_error(
+            next_free_edition(&master_edition(9, Some(10)), 11, 1, markers),
+            MplCpiError::MaxSupplyReached,
+        );
+        assert_error(
+            next_free_edition(&master_edition(10, Some(10)), 1, 1, markers),
+            MplCpiError::MaxSupplyReached,
+        );
+    }
+
+    #[test]
+    fn next_free_edition_runs_out_of_markers() {
+        let master_edition = master_edition(495, None);
+        let markers = |_| Ok(Some([0xff; 31]));
+        assert_error(
+            next_free_edition(&master_edition, 1, 2, markers),
+            MplCpiError::EditionScanExhausted,
+        );
+        assert_error(
+            next_free_edition(&master_edition, 1, 0, markers),
+            MplCpiError::EditionScanExhausted,
+        );
+    }
+
+    #[test]
+    fn next_free_edition_does_not_overflow() {
+        let master_edition = master_edition(0, None);
+        assert_eq!(
+            next_free_edition(&master_edition, u64::MAX, 3, |_| Ok(None)).unwrap(),
+            u64::MAX
+        );
+        assert_error(
+            next_free_edition(&master_edition, u64::MAX, 3, |_| Ok(Some([0xff; 31]))),
+            MplCpiError::MaxSupplyReached,
+        );
+    }
+}
@@ -0,0 +1,67 @@
+use anchor_lang::prelude::*;
+
+#[error_code]
+pub enum MplCpiError {
+    #[msg("Edition marker account does not match the derived address")]
+    InvalidEditionMarker,
+    #[msg("Edition number must be greater than zero")]
+    InvalidEditionNumber,
+    #[msg("Master edition has no editions left to print")]
+    MaxSupplyReached,
+    #[msg("No free edition found within the scanned edition markers")]
+    EditionScanExhausted,
+    #[msg("Edition account is not the expected edition PDA")]
+    InvalidEditionAccount,
+    #[msg("Collection authority record does not match the derived address")]
+    InvalidCollectionAuthorityRecord,
+    #[msg("Collection authority record has not been approved")]
+    CollectionAuthorityRecordMissing,
+    #[msg("Use authority record does not match the derived address")]
+    InvalidUseAuthorityRecord,
+    #[msg("Burner account does not match the derived address")]
+    InvalidBurner,
+    #[msg("Use authority has not been delegated enough uses")]
+    InsufficientDelegatedUses,
+    #[msg("Metadata has no uses configured")]
+    Unusable,
+    #[msg("Metadata does not have enough remaining uses")]
+    NotEnoughUses,
+    #[msg("Single use items must be used exactly once")]
+    InvalidNumberOfUses,
+    #[msg("Account is not listed as a creator on the metadata")]
+    CreatorNotFound,
+    #[msg("Metadata account is not the metadata PDA of the mint")]
+    InvalidMetadataAccount,
+    #[msg("Metadata does not belong to the expected collection")]
+    CollectionMismatch,
+    #[msg("Metadata collection is not verified")]
+    CollectionNotVerified,
+    #[msg("Creator has not verified the metadata")]
+    CreatorNotVerified,
+    #[msg("NFT mints must have zero decimals")]
+    InvalidMintDecimals,
+    #[msg("NFT mints must have a supply of one")]
+    InvalidMintSupply,
+    #[msg("Mint authority is not the edition PDA")]
+    MintAuthorityNotEdition,
+    #[msg("Creator shares do not add up to 100")]
+    InvalidCreatorShares,
+    #[msg("Seller fee basis points exceed 10000")]
+    InvalidSellerFeeBasisPoints,
+    #[msg("Royalty recipient accounts do not match the metadata creators")]
+    InvalidRoyaltyRecipient,
+    #[msg("Numerical overflow")]
+    NumericalOverflow,
+    #[msg("Remaining accounts do not split into whole account groups")]
+    InvalidRemainingAccounts,
+    #[msg("Mint authority account is not the mint's authority")]
+    MintAuthorityMismatch,
+    #[msg("Mint is backed by an edition")]
+    MintHasEdition,
+    #[msg("Token standard cannot be determined from the mint and edition")]
+    UnknownTokenStandard,
+    #[msg("Token record account does not match the derived address")]
+    InvalidTokenRecord,
+    #[msg("Authorization rules account is not the rule set of the metadata")]
+    InvalidRuleSet,
+}
@@ -0,0 +1,137 @@
+use anchor_lang::prelude::*;
+use anchor_lang::solana_program::program_option::COption;
+use anchor_spl::token::Mint;
+use mpl_token_metadata::pda::find_master_edition_account;
+use mpl_token_metadata::state::DataV2;
+
+use crate::assertions::assert_metadata_for_mint;
+use crate::error::MplCpiError;
+use crate::reader::MetadataReader;
+use crate::types::{Creator, Uses};
+use crate::{CreateMetadataAccountV3, UpdateMetadataAccountsV2};
+
+/// Attaches metadata to an existing SPL mint, e.g. one whose mint authority is a program PDA.
+///
+/// No master edition, collection or collection details are set, so the metadata program
+/// records the mint as `FungibleAsset` when it has zero decimals and `Fungible` otherwise.
+/// `mint_authority` must be the mint's current authority. Set `update_authority_is_signer` when
+/// `update_authority` signs, as a keypair or through `ctx.signer_seeds`.
+pub fn create_fungible_metadata<'a, 'b, 'c, 'info>(
+    ctx: CpiContext<'a, 'b, 'c, 'info, CreateFungibleMetadata<'info>>,
+    name: String,
+    symbol: String,
+    uri: String,
+    seller_fee_basis_points: u16,
+    creators: Option<Vec<Creator>>,
+    uses: Option<Uses>,
+    update_authority_is_signer: bool,
+    is_mutable: bool,
+) -> Result<()> {
+    let accounts = ctx.accounts;
+    if accounts.mint.mint_authority != COption::Some(accounts.mint_authority.key()) {
+        return err!(MplCpiError::MintAuthorityMismatch);
+    }
+    crate::create_metadata_accounts_v3(
+        CpiContext::new_with_signer(
+            ctx.program,
+            CreateMetadataAccountV3 {
+                metadata_account: accounts.metadata,
+                mint: accounts.mint.to_account_info(),
+                mint_authority: accounts.mint_authority,
+                payer: accounts.payer,
+                update_authority: accounts.update_authority,
+                system_program: accounts.system_program,
+                rent: accounts.rent,
+            },
+            ctx.signer_seeds,
+        ),
+        name,
+        symbol,
+        uri,
+        seller_fee_basis_points,
+        update_authority_is_signer,
+        is_mutable,
+        uses.map(Into::into),
+        None,
+        creators.map(|creators| creators.into_iter().map(Into::into).collect()),
+        None,
+    )
+}
+
+#[derive(Accounts)]
+pub struct CreateFungibleMetadata<'info> {
+    /// CHECK: metadata PDA of the mint, validated by the metadata program
+    #[account(mut)]
+    pub metadata: AccountInfo<'info>,
+    pub mint: Account<'info, Mint>,
+    /// CHECK: current mint authority of the mint
+    #[account(signer)]
+    pub mint_authority: AccountInfo<'info>,
+    /// CHECK: pays for the metadata account
+    #[account(mut, signer)]
+    pub payer: AccountInfo<'info>,
+    /// CHECK: update authority to set, signs when `update_authority_is_signer`
+    pub update_authority: AccountInfo<'info>,
+    pub system_program: Program<'info, System>,
+    pub rent: Sysvar<'info, Rent>,
+}
+
+/// Replaces the data of metadata created with [`create_fungible_metadata`].
+///
+/// The collection stays unset and the update authority, primary sale and mutability are left
+/// as they are. Mints backed by an edition are rejected.
+pub fn update_fungible_metadata<'a, 'b, 'c, 'info>(
+    ctx: CpiContext<'a, 'b, 'c, 'info, UpdateFungibleMetadata<'info>>,
+    name: String,
+    symbol: String,
+    uri: String,
+    seller_fee_basis_points: u16,
+    creators: Option<Vec<Creator>>,
+    uses: Option<Uses>,
+) -> Result<()> {
+    let accounts = ctx.accounts;
+    let mint = accounts.mint.key();
+    assert_metadata_for_mint(&accounts.metadata, &mint)?;
+    let (edition, _) = find_master_edition_account(&mint);
+    if accounts.mint.mint_authority == COption::Some(edition) {
+        return err!(MplCpiError::MintHasEdition);
+    }
+    let (primary_sale_happened, is_mutable) = {
+        let data = accounts.metadata.try_borrow_data()?;
+        let reader = MetadataReader::new(&data)?;
+        (reader.primary_sale_happened()?, reader.is_mutable()?)
+    };
+    crate::update_metadata_accounts_v2(
+        CpiContext::new_with_signer(
+            ctx.program,
+            UpdateMetadataAccountsV2 {
+                metadata_account: accounts.metadata,
+                update_authority: accounts.update_authority.clone(),
+                new_update_authority: accounts.update_authority,
+            },
+            ctx.signer_seeds,
+        ),
+        DataV2 {
+            name,
+            symbol,
+            uri,
+            seller_fee_basis_points,
+            creators: creators.map(|creators| creators.into_iter().map(Into::into).collect()),
+            collection: None,
+            uses: uses.map(Into::into),
+        },
+        primary_sale_happened,
+        is_mutable,
+    )
+}
+
+#[derive(Accounts)]
+pub struct UpdateFungibleMetadata<'info> {
+    /// CHECK: metadata PDA of the mint, checked against `mint`
+    #[account(mut)]
+    pub metadata: AccountInfo<'info>,
+    /// CHECK: update authority of the metadata
+    #[account(signer)]
+    pub update_authority: AccountInfo<'info>,
+    pub mint: Account<'info, Mint>,
+}
@@ -1,11 +1,12 @@
+#![allow(clippy::result_large_err)]
+#![allow(clippy::too_many_arguments)]
+
 use anchor_lang::prelude::*;
 use anchor_lang::{solana_program, Result};
-use anchor_spl::{
-    associated_token::{self, AssociatedToken, Create},
-    token::{self, Mint, MintTo, Token},
-};
+use anchor_spl::{associated_token::AssociatedToken, token::Token};
 use mpl_token_metadata::instruction;
 use mpl_token_metadata::state::Collection;
+use mpl_token_metadata::state::CollectionDetails;
 use mpl_token_metadata::state::Creator;
 use mpl_token_metadata::state::Data;
 use mpl_token_metadata::state::DataV2;
@@ -13,6 +14,21 @@ use mpl_token_metadata::state::DataV2;
 use mpl_token_metadata::state::Uses;
 pub use mpl_token_metadata::ID;
 
+pub mod assertions;
+pub mod batch;
+pub mod collection;
+pub mod creators;
+pub mod edition;
+pub mod error;
+pub mod fungible;
+mod mint;
+pub mod programmable;
+pub mod reader;
+pub mod royalties;
+pub mod token_standard;
+pub mod types;
+pub mod uses;
+
 pub const PREFIX: &str = "metadata";
 pub const EDITION: &str = "edition";
 pub const EDITION_MARKER_BIT_SIZE: u64 = 248;
@@ -64,10 +80,84 @@ pub fn create_metadata_accounts_v2<'a, 'b, 'c, 'info>(
 
 #[derive(Accounts)]
 pub struct CreateMetadataAccountV2<'info> {
+    /// CHECK: metadata PDA of the mint, validated by the metadata program
+    #[account(mut)]
+    pub metadata_account: AccountInfo<'info>,
+    /// CHECK: mint of the NFT, validated by the metadata program
+    pub mint: AccountInfo<'info>,
+    /// CHECK: current mint authority of the mint
+    #[account(signer)]
+    pub mint_authority: AccountInfo<'info>,
+    /// CHECK: pays for any accounts created
+    #[account(mut, signer)]
+    pub payer: AccountInfo<'info>,
+    /// CHECK: update authority to set, signs when `update_authority_is_signer`
+    pub update_authority: AccountInfo<'info>,
+    pub system_program: Program<'info, System>,
+    pub rent: Sysvar<'info, Rent>,
+}
+
+pub fn create_metadata_accounts_v3<'a, 'b, 'c, 'info>(
+    ctx: CpiContext<'a, 'b, 'c, 'info, CreateMetadataAccountV3<'info>>,
+    name: String,
+    symbol: String,
+    uri: String,
+    seller_fee_basis_points: u16,
+    update_authority_is_signer: bool,
+    is_mutable: bool,
+    uses: Option<Uses>,
+    collection: Option<Collection>,
+    creators: Option<Vec<Creator>>,
+    collection_details: Option<CollectionDetails>,
+) -> Result<()> {
+    let ix = instruction::create_metadata_accounts_v3(
+        mpl_token_metadata::ID,
+        ctx.accounts.metadata_account.key(),
+        ctx.accounts.mint.key(),
+        ctx.accounts.mint_authority.key(),
+        ctx.accounts.payer.key(),
+        ctx.accounts.update_authority.key(),
+        name,
+        symbol,
+        uri,
+        creators,
+        seller_fee_basis_points,
+        update_authority_is_signer,
+        is_mutable,
+        collection,
+        uses,
+        collection_details,
+    );
+    solana_program::program::invoke_signed(
+        &ix,
+        &[
+            ctx.accounts.metadata_account,
+            ctx.accounts.mint,
+            ctx.accounts.mint_authority,
+            ctx.accounts.payer,
+            ctx.accounts.update_authority,
+            ctx.accounts.system_program.to_account_info(),
+            ctx.accounts.rent.to_account_info(),
+        ],
+        ctx.signer_seeds,
+    )
+    .map_err(Into::into)
+}
+
+#[derive(Accounts)]
+pub struct CreateMetadataAccountV3<'info> {
+    /// CHECK: metadata PDA of the mint, validated by the metadata program
+    #[account(mut)]
     pub metadata_account: AccountInfo<'info>,
+    /// CHECK: mint of the NFT, validated by the metadata program
     pub mint: AccountInfo<'info>,
+    /// CHECK: current mint authority of the mint
+    #[account(signer)]
     pub mint_authority: AccountInfo<'info>,
+    /// CHECK: pays for any accounts created
+    #[account(mut, signer)]
     pub payer: AccountInfo<'info>,
+    /// CHECK: update authority to set, signs when `update_authority_is_signer`
     pub update_authority: AccountInfo<'info>,
     pub system_program: Program<'info, System>,
     pub rent: Sysvar<'info, Rent>,
@@ -116,10 +206,18 @@ pub fn create_metadata_accounts<'a, 'b, 'c, 'info>(
 
 #[derive(Accounts)]
 pub struct CreateMetadataAccounts<'info> {
+    /// CHECK: metadata PDA of the mint, validated by the metadata program
+    #[account(mut)]
     pub metadata_account: AccountInfo<'info>,
+    /// CHECK: mint of the NFT, validated by the metadata program
     pub mint: AccountInfo<'info>,
+    /// CHECK: current mint authority of the mint
+    #[account(signer)]
     pub mint_authority: AccountInfo<'info>,
+    /// CHECK: pays for any accounts created
+    #[account(mut, signer)]
     pub payer: AccountInfo<'info>,
+    /// CHECK: update authority to set, signs when `update_authority_is_signer`
     pub update_authority: AccountInfo<'info>,
     pub system_program: Program<'info, System>,
     pub rent: Sysvar<'info, Rent>,
@@ -148,8 +246,13 @@ pub fn update_metadata_accounts<'a, 'b, 'c, 'info>(
 
 #[derive(Accounts)]
 pub struct UpdateMetadataAccounts<'info> {
+    /// CHECK: metadata PDA of the mint, validated by the metadata program
+    #[account(mut)]
     pub metadata_account: AccountInfo<'info>,
+    /// CHECK: update authority of the metadata
+    #[account(signer)]
     pub update_authority: AccountInfo<'info>,
+    /// CHECK: update authority to set on the metadata
     pub new_update_authority: AccountInfo<'info>,
 }
 
@@ -178,8 +281,13 @@ pub fn update_metadata_accounts_v2<'a, 'b, 'c, 'info>(
 
 #[derive(Accounts)]
 pub struct UpdateMetadataAccountsV2<'info> {
+    /// CHECK: metadata PDA of the mint, validated by the metadata program
+    #[account(mut)]
     pub metadata_account: AccountInfo<'info>,
+    /// CHECK: update authority of the metadata
+    #[account(signer)]
     pub update_authority: AccountInfo<'info>,
+    /// CHECK: update authority to set on the metadata
     pub new_update_authority: AccountInfo<'info>,
 }
 
@@ -206,14 +314,19 @@ pub fn update_primary_sale_happened_via_token<'a, 'b, 'c, 'info>(
 
 #[derive(Accounts)]
 pub struct UpdatePrimarySaleHappenedViaToken<'info> {
+    /// CHECK: metadata PDA of the mint, validated by the metadata program
+    #[account(mut)]
     pub metadata_account: AccountInfo<'info>,
+    /// CHECK: owner of the NFT token account
+    #[account(signer)]
     pub owner: AccountInfo<'info>,
+    /// CHECK: token account holding the NFT
     pub token: AccountInfo<'info>,
 }
 
 pub fn create_master_edition<'a, 'b, 'c, 'info>(
     ctx: CpiContext<'a, 'b, 'c, 'info, CreateMasterEdition<'info>>,
-    max_supply: u64,
+    max_supply: Option<u64>,
 ) -> Result<()> {
     let ix = instruction::create_master_edition(
         mpl_token_metadata::ID,
@@ -223,7 +336,7 @@ pub fn create_master_edition<'a, 'b, 'c, 'info>(
         ctx.accounts.mint_authority.key(),
         ctx.accounts.metadata.key(),
         ctx.accounts.payer.key(),
-        Some(max_supply),
+        max_supply,
     );
     solana_program::program::invoke_signed(
         &ix,
@@ -245,11 +358,22 @@ pub fn create_master_edition<'a, 'b, 'c, 'info>(
 
 #[derive(Accounts)]
 pub struct CreateMasterEdition<'info> {
+    /// CHECK: edition PDA of the mint
+    #[account(mut)]
     pub edition: AccountInfo<'info>,
+    /// CHECK: mint of the NFT, validated by the metadata program
+    #[account(mut)]
     pub mint: AccountInfo<'info>,
+    /// CHECK: update authority of the metadata
+    #[account(signer)]
     pub update_authority: AccountInfo<'info>,
+    /// CHECK: current mint authority of the mint
+    #[account(signer)]
     pub mint_authority: AccountInfo<'info>,
+    /// CHECK: metadata PDA of the mint
     pub metadata: AccountInfo<'info>,
+    /// CHECK: pays for any accounts created
+    #[account(mut, signer)]
     pub payer: AccountInfo<'info>,
     pub system_program: Program<'info, System>,
     pub token_program: Program<'info, Token>,
@@ -258,7 +382,7 @@ pub struct CreateMasterEdition<'info> {
 
 pub fn create_master_edition_v3<'a, 'b, 'c, 'info>(
     ctx: CpiContext<'a, 'b, 'c, 'info, CreateMasterEditionV3<'info>>,
-    max_supply: u64,
+    max_supply: Option<u64>,
 ) -> Result<()> {
     let ix = instruction::create_master_edition_v3(
         mpl_token_metadata::ID,
@@ -268,7 +392,7 @@ pub fn create_master_edition_v3<'a, 'b, 'c, 'info>(
         ctx.accounts.mint_authority.key(),
         ctx.accounts.metadata.key(),
         ctx.accounts.payer.key(),
-        Some(max_supply),
+        max_supply,
     );
     solana_program::program::invoke_signed(
         &ix,
@@ -290,91 +414,114 @@ pub fn create_master_edition_v3<'a, 'b, 'c, 'info>(
 
 #[derive(Accounts)]
 pub struct CreateMasterEditionV3<'info> {
+    /// CHECK: edition PDA of the mint
+    #[account(mut)]
     pub edition: AccountInfo<'info>,
+    /// CHECK: mint of the NFT, validated by the metadata program
+    #[account(mut)]
     pub mint: AccountInfo<'info>,
+    /// CHECK: update authority of the metadata
+    #[account(signer)]
     pub update_authority: AccountInfo<'info>,
+    /// CHECK: current mint authority of the mint
+    #[account(signer)]
     pub mint_authority: AccountInfo<'info>,
+    /// CHECK: metadata PDA of the mint
+    #[account(mut)]
     pub metadata: AccountInfo<'info>,
+    /// CHECK: pays for any accounts created
+    #[account(mut, signer)]
     pub payer: AccountInfo<'info>,
     pub system_program: Program<'info, System>,
     pub token_program: Program<'info, Token>,
     pub rent: Sysvar<'info, Rent>,
 }
 
-// pub fn mint_new_edition_from_master_edition_via_token<'a, 'b, 'c, 'info>(
-//     ctx: CpiContext<'a, 'b, 'c, 'info, MintNewEditionFromMasterEditionViaToken<'info>>,
-//     edition: u64,
-// ) -> Result<()> {
-//     let ix = instruction::mint_new_edition_from_master_edition_via_token(
-//         mpl_token_metadata::ID,
-//        ctx.accounts.new_metadata.key(),
-//        ctx.accounts.new_edition.key(),
-//        ctx.accounts.master_edition.key(),
-//        ctx.accounts.new_mint.key(),
-//        ctx.accounts.new_mint_authority.key(),
-//        ctx.accounts.payer.key(),
-//        ctx.accounts.token_account_owner.key(),
-//        ctx.accounts.token_account.key(),
-//        ctx.accounts.new_metadata_update_authority.key(),
-//        ctx.accounts.metadata.key(),
-//        ctx.accounts.metadata_mint.key(),
-//        edition
-//     );
-//     let edition_number = edition.checked_div(EDITION_MARKER_BIT_SIZE).unwrap();
-//     let as_string = edition_number.to_string();
-//     let (edition_mark_pda, _) = Pubkey::find_program_address(
-//         &[
-//             PREFIX.as_bytes(),
-//             mpl_token_metadata::ID.as_ref(),
-//             ctx.accounts.metadata_mint.key().as_ref(),
-//             EDITION.as_bytes(),
-//             as_string.as_bytes(),
-//         ],
-//         &mpl_token_metadata::ID,
-//     );
-//     // AccountMeta::new(new_metadata, false),
-//     // AccountMeta::new(new_edition, false),
-//     // AccountMeta::new(master_edition, false),
-//     // AccountMeta::new(new_mint, false),
-//     // AccountMeta::new(edition_mark_pda, false),
-//     // AccountMeta::new_readonly(new_mint_authority, true),
-//     // AccountMeta::new(payer, true),
-//     // AccountMeta::new_readonly(token_account_owner, true),
-//     // AccountMeta::new_readonly(token_account, false),
-//     // AccountMeta::new_readonly(new_metadata_update_authority, false),
-//     // AccountMeta::new_readonly(metadata, false),
-//     // AccountMeta::new_readonly(spl_token::id(), false),
-//     // AccountMeta::new_readonly(solana_program::system_program::id(), false),
-//     // AccountMeta::new_readonly(sysvar::rent::id(), false),
-//     solana_program::program::invoke_signed(
-//         &ix,
-//         &[
-//            ctx.accounts.new_metadata,
-//             ctx.accounts.new_edition,
-//             ctx.accounts.master_edition,
-//             ctx.accounts.new_mint,
-//             edition_mark_pda.clone(),
-
-//         ],
-//         ctx.signer_seeds,
-//     )
-//     .map_err(Into::into)
-// }
-
-// #[derive(Accounts)]
-// pub struct MintNewEditionFromMasterEditionViaToken<'info> {
-//     pub new_metadata: AccountInfo<'info>,
-//     pub new_edition: AccountInfo<'info>,
-//     pub master_edition: AccountInfo<'info>,
-//     pub new_mint: AccountInfo<'info>,
-//     pub new_mint_authority: AccountInfo<'info>,
-//     pub payer: AccountInfo<'info>,
-//     pub token_account_owner: AccountInfo<'info>,
-//     pub token_account: AccountInfo<'info>,
-//     pub new_metadata_update_authority: AccountInfo<'info>,
-//     pub metadata: AccountInfo<'info>,
-//     pub metadata_mint: AccountInfo<'info>
-// }
+pub fn mint_new_edition_from_master_edition_via_token<'a, 'b, 'c, 'info>(
+    ctx: CpiContext<'a, 'b, 'c, 'info, MintNewEditionFromMasterEditionViaToken<'info>>,
+    edition: u64,
+) -> Result<()> {
+    let (edition_marker, _) =
+        edition::find_edition_marker_account(&ctx.accounts.metadata_mint.key(), edition);
+    if ctx.accounts.edition_mark_pda.key() != edition_marker {
+        return err!(error::MplCpiError::InvalidEditionMarker);
+    }
+    let ix = instruction::mint_new_edition_from_master_edition_via_token(
+        mpl_token_metadata::ID,
+        ctx.accounts.new_metadata.key(),
+        ctx.accounts.new_edition.key(),
+        ctx.accounts.master_edition.key(),
+        ctx.accounts.new_mint.key(),
+        ctx.accounts.new_mint_authority.key(),
+        ctx.accounts.payer.key(),
+        ctx.accounts.token_account_owner.key(),
+        ctx.accounts.token_account.key(),
+        ctx.accounts.new_metadata_update_authority.key(),
+        ctx.accounts.metadata.key(),
+        ctx.accounts.metadata_mint.key(),
+        edition,
+    );
+    solana_program::program::invoke_signed(
+        &ix,
+        &[
+            ctx.accounts.new_metadata,
+            ctx.accounts.new_edition,
+            ctx.accounts.master_edition,
+            ctx.accounts.new_mint,
+            ctx.accounts.edition_mark_pda,
+            ctx.accounts.new_mint_authority,
+            ctx.accounts.payer,
+            ctx.accounts.token_account_owner,
+            ctx.accounts.token_account,
+            ctx.accounts.new_metadata_update_authority,
+            ctx.accounts.metadata,
+            ctx.accounts.token_program.to_account_info(),
+            ctx.accounts.system_program.to_account_info(),
+            ctx.accounts.rent.to_account_info(),
+        ],
+        ctx.signer_seeds,
+    )
+    .map_err(Into::into)
+}
+
+#[derive(Accounts)]
+pub struct MintNewEditionFromMasterEditionViaToken<'info> {
+    /// CHECK: metadata PDA of the new edition mint
+    #[account(mut)]
+    pub new_metadata: AccountInfo<'info>,
+    /// CHECK: edition PDA of the new edition mint
+    #[account(mut)]
+    pub new_edition: AccountInfo<'info>,
+    /// CHECK: master edition PDA, validated by the metadata program
+    #[account(mut)]
+    pub master_edition: AccountInfo<'info>,
+    /// CHECK: mint of the new edition
+    #[account(mut)]
+    pub new_mint: AccountInfo<'info>,
+    /// CHECK: edition marker PDA, checked against the edition number before the CPI
+    #[account(mut)]
+    pub edition_mark_pda: AccountInfo<'info>,
+    /// CHECK: mint authority of the new edition mint
+    #[account(signer)]
+    pub new_mint_authority: AccountInfo<'info>,
+    /// CHECK: pays for any accounts created
+    #[account(mut, signer)]
+    pub payer: AccountInfo<'info>,
+    /// CHECK: owner of the token account holding the master edition
+    #[account(signer)]
+    pub token_account_owner: AccountInfo<'info>,
+    /// CHECK: token account holding the master edition
+    pub token_account: AccountInfo<'info>,
+    /// CHECK: update authority of the master edition metadata
+    pub new_metadata_update_authority: AccountInfo<'info>,
+    /// CHECK: metadata PDA of the master edition
+    pub metadata: AccountInfo<'info>,
+    /// CHECK: mint of the master edition
+    pub metadata_mint: AccountInfo<'info>,
+    pub system_program: Program<'info, System>,
+    pub token_program: Program<'info, Token>,
+    pub rent: Sysvar<'info, Rent>,
+}
 
 pub fn sign_metadata<'a, 'b, 'c, 'info>(
     ctx: CpiContext<'a, 'b, 'c, 'info, SignMetadata<'info>>,
@@ -394,7 +541,11 @@ pub fn sign_metadata<'a, 'b, 'c, 'info>(
 
 #[derive(Accounts)]
 pub struct SignMetadata<'info> {
+    /// CHECK: metadata PDA, validated by the metadata program
+    #[account(mut)]
     pub metadata: AccountInfo<'info>,
+    /// CHECK: creator listed on the metadata
+    #[account(signer)]
     pub creator: AccountInfo<'info>,
 }
 
@@ -416,14 +567,31 @@ pub fn remove_creator_verification<'a, 'b, 'c, 'info>(
 
 #[derive(Accounts)]
 pub struct RemoveCreatorVerification<'info> {
+    /// CHECK: metadata PDA, validated by the metadata program
+    #[account(mut)]
     pub metadata: AccountInfo<'info>,
+    /// CHECK: creator listed on the metadata
+    #[account(signer)]
     pub creator: AccountInfo<'info>,
 }
 
+#[allow(deprecated)]
 pub fn mint_edition_from_master_edition_via_vault_proxy<'a, 'b, 'c, 'info>(
     ctx: CpiContext<'a, 'b, 'c, 'info, MintEditionFromMasterEditionViaVaultProxy<'info>>,
     edition: u64,
 ) -> Result<()> {
+    // The master mint sits right after the key and update authority in the metadata account.
+    let metadata_mint = {
+        let data = ctx.accounts.metadata.try_borrow_data()?;
+        let bytes = data
+            .get(33..65)
+            .ok_or(ErrorCode::AccountDidNotDeserialize)?;
+        Pubkey::new(bytes)
+    };
+    let (edition_marker, _) = edition::find_edition_marker_account(&metadata_mint, edition);
+    if ctx.accounts.edition_mark_pda.key() != edition_marker {
+        return err!(error::MplCpiError::InvalidEditionMarker);
+    }
     let ix = instruction::mint_edition_from_master_edition_via_vault_proxy(
         mpl_token_metadata::ID,
         ctx.accounts.new_metadata.key(),
@@ -440,7 +608,7 @@ pub fn mint_edition_from_master_edition_via_vault_proxy<'a, 'b, 'c, 'info>(
         ctx.accounts.new_metadata_update_authority.key(),
         ctx.accounts.metadata.key(),
         ctx.accounts.token_program.key(),
-        ctx.accounts.token_vault_program_info.key(),
+        ctx.accounts.token_vault_program.key(),
         edition,
     );
     solana_program::program::invoke_signed(
@@ -459,8 +627,8 @@ pub fn mint_edition_from_master_edition_via_vault_proxy<'a, 'b, 'c, 'info>(
             ctx.accounts.vault,
             ctx.accounts.new_metadata_update_authority,
             ctx.accounts.metadata,
-            ctx.accounts.token_program,
-            ctx.accounts.token_vault_program_info,
+            ctx.accounts.token_program.to_account_info(),
+            ctx.accounts.token_vault_program.to_account_info(),
             ctx.accounts.system_program.to_account_info(),
             ctx.accounts.rent.to_account_info(),
         ],
@@ -471,25 +639,55 @@ pub fn mint_edition_from_master_edition_via_vault_proxy<'a, 'b, 'c, 'info>(
 
 #[derive(Accounts)]
 pub struct MintEditionFromMasterEditionViaVaultProxy<'info> {
+    /// CHECK: metadata PDA of the new edition mint
+    #[account(mut)]
     pub new_metadata: AccountInfo<'info>,
+    /// CHECK: edition PDA of the new edition mint
+    #[account(mut)]
     pub new_edition: AccountInfo<'info>,
+    /// CHECK: master edition PDA, validated by the metadata program
+    #[account(mut)]
     pub master_edition: AccountInfo<'info>,
+    /// CHECK: mint of the new edition
+    #[account(mut)]
     pub new_mint: AccountInfo<'info>,
+    /// CHECK: edition marker PDA, checked against the edition number before the CPI
+    #[account(mut)]
     pub edition_mark_pda: AccountInfo<'info>,
+    /// CHECK: mint authority of the new edition mint
+    #[account(signer)]
     pub new_mint_authority: AccountInfo<'info>,
+    /// CHECK: pays for any accounts created
+    #[account(mut, signer)]
     pub payer: AccountInfo<'info>,
+    /// CHECK: authority of the token vault
+    #[account(signer)]
     pub vault_authority: AccountInfo<'info>,
+    /// CHECK: safety deposit store holding the master edition, validated by the vault program
     pub safety_deposit_store: AccountInfo<'info>,
+    /// CHECK: safety deposit box of the vault, validated by the vault program
     pub safety_deposit_box: AccountInfo<'info>,
+    /// CHECK: token vault holding the master edition
     pub vault: AccountInfo<'info>,
+    /// CHECK: update authority of the master edition metadata
     pub new_metadata_update_authority: AccountInfo<'info>,
+    /// CHECK: metadata PDA of the master edition
     pub metadata: AccountInfo<'info>,
-    pub token_program: AccountInfo<'info>,
-    pub token_vault_program_info: AccountInfo<'info>,
+    pub token_program: Program<'info, Token>,
+    pub token_vault_program: Program<'info, TokenVault>,
     pub system_program: Program<'info, System>,
     pub rent: Sysvar<'info, Rent>,
 }
 
+#[derive(Clone)]
+pub struct TokenVault;
+
+impl anchor_lang::Id for TokenVault {
+    fn id() -> Pubkey {
+        mpl_token_vault::ID
+    }
+}
+
 pub fn convert_master_edition_v1_to_v2<'a, 'b, 'c, 'info>(
     ctx: CpiContext<'a, 'b, 'c, 'info, ConvertMasterEditionV1toV2<'info>>,
 ) -> Result<()> {
@@ -513,8 +711,14 @@ pub fn convert_master_edition_v1_to_v2<'a, 'b, 'c, 'info>(
 
 #[derive(Accounts)]
 pub struct ConvertMasterEditionV1toV2<'info> {
+    /// CHECK: master edition PDA, validated by the metadata program
+    #[account(mut)]
     pub master_edition: AccountInfo<'info>,
+    /// CHECK: one time printing authorization mint of the v1 master edition
+    #[account(mut)]
     pub one_time_auth: AccountInfo<'info>,
+    /// CHECK: printing mint of the v1 master edition
+    #[account(mut)]
     pub printing_mint: AccountInfo<'info>,
 }
 
@@ -529,7 +733,7 @@ pub fn verify_collection<'a, 'b, 'c, 'info>(
         ctx.accounts.collection_mint.key(),
         ctx.accounts.collection.key(),
         ctx.accounts.collection_master_edition_account.key(),
-        Some(ctx.accounts.collection_authority_record.key()),
+        approved_collection_authority_record(&ctx.accounts.collection_authority_record),
     );
 
     solana_program::program::invoke_signed(
@@ -541,6 +745,7 @@ pub fn verify_collection<'a, 'b, 'c, 'info>(
             ctx.accounts.collection_mint,
             ctx.accounts.collection,
             ctx.accounts.collection_master_edition_account,
+            ctx.accounts.collection_authority_record,
         ],
         ctx.signer_seeds,
     )
@@ -549,12 +754,74 @@ pub fn verify_collection<'a, 'b, 'c, 'info>(
 
 #[derive(Accounts)]
 pub struct VerifyCollection<'info> {
+    /// CHECK: metadata PDA, validated by the metadata program
+    #[account(mut)]
     pub metadata: AccountInfo<'info>,
+    /// CHECK: update authority or approved delegate of the collection
+    #[account(mut, signer)]
     pub collection_authority: AccountInfo<'info>,
+    /// CHECK: pays for any accounts created
+    #[account(mut, signer)]
     pub payer: AccountInfo<'info>,
+    /// CHECK: mint of the collection NFT
     pub collection_mint: AccountInfo<'info>,
+    /// CHECK: metadata PDA of the collection NFT
     pub collection: AccountInfo<'info>,
+    /// CHECK: master edition PDA of the collection NFT
     pub collection_master_edition_account: AccountInfo<'info>,
+    /// CHECK: collection authority record PDA of the delegate
+    pub collection_authority_record: AccountInfo<'info>,
+}
+
+pub fn verify_sized_collection_item<'a, 'b, 'c, 'info>(
+    ctx: CpiContext<'a, 'b, 'c, 'info, VerifySizedCollectionItem<'info>>,
+) -> Result<()> {
+    let ix = instruction::verify_sized_collection_item(
+        mpl_token_metadata::ID,
+        ctx.accounts.metadata.key(),
+        ctx.accounts.collection_authority.key(),
+        ctx.accounts.payer.key(),
+        ctx.accounts.collection_mint.key(),
+        ctx.accounts.collection.key(),
+        ctx.accounts.collection_master_edition_account.key(),
+        approved_collection_authority_record(&ctx.accounts.collection_authority_record),
+    );
+
+    solana_program::program::invoke_signed(
+        &ix,
+        &[
+            ctx.accounts.metadata,
+            ctx.accounts.collection_authority,
+            ctx.accounts.payer,
+            ctx.accounts.collection_mint,
+            ctx.accounts.collection,
+            ctx.accounts.collection_master_edition_account,
+            ctx.accounts.collection_authority_record,
+        ],
+        ctx.signer_seeds,
+    )
+    .map_err(Into::into)
+}
+
+#[derive(Accounts)]
+pub struct VerifySizedCollectionItem<'info> {
+    /// CHECK: metadata PDA, validated by the metadata program
+    #[account(mut)]
+    pub metadata: AccountInfo<'info>,
+    /// CHECK: update authority or approved delegate of the collection
+    #[account(signer)]
+    pub collection_authority: AccountInfo<'info>,
+    /// CHECK: pays for any accounts created
+    #[account(mut, signer)]
+    pub payer: AccountInfo<'info>,
+    /// CHECK: mint of the collection NFT
+    pub collection_mint: AccountInfo<'info>,
+    /// CHECK: metadata PDA of the collection NFT, its size is incremented
+    #[account(mut)]
+    pub collection: AccountInfo<'info>,
+    /// CHECK: master edition PDA of the collection NFT
+    pub collection_master_edition_account: AccountInfo<'info>,
+    /// CHECK: collection authority record PDA of the delegate
     pub collection_authority_record: AccountInfo<'info>,
 }
 
@@ -568,7 +835,7 @@ pub fn unverify_collection<'a, 'b, 'c, 'info>(
         ctx.accounts.collection_mint.key(),
         ctx.accounts.collection.key(),
         ctx.accounts.collection_master_edition_account.key(),
-        Some(ctx.accounts.collection_authority_record.key()),
+        approved_collection_authority_record(&ctx.accounts.collection_authority_record),
     );
 
     solana_program::program::invoke_signed(
@@ -579,6 +846,7 @@ pub fn unverify_collection<'a, 'b, 'c, 'info>(
             ctx.accounts.collection_mint,
             ctx.accounts.collection,
             ctx.accounts.collection_master_edition_account,
+            ctx.accounts.collection_authority_record,
         ],
         ctx.signer_seeds,
     )
@@ -587,11 +855,19 @@ pub fn unverify_collection<'a, 'b, 'c, 'info>(
 
 #[derive(Accounts)]
 pub struct UnverifyCollection<'info> {
+    /// CHECK: metadata PDA, validated by the metadata program
+    #[account(mut)]
     pub metadata: AccountInfo<'info>,
+    /// CHECK: update authority or approved delegate of the collection
+    #[account(mut, signer)]
     pub collection_authority: AccountInfo<'info>,
+    /// CHECK: mint of the collection NFT
     pub collection_mint: AccountInfo<'info>,
+    /// CHECK: metadata PDA of the collection NFT
     pub collection: AccountInfo<'info>,
+    /// CHECK: master edition PDA of the collection NFT
     pub collection_master_edition_account: AccountInfo<'info>,
+    /// CHECK: collection authority record PDA of the delegate
     pub collection_authority_record: AccountInfo<'info>,
 }
 
@@ -599,44 +875,59 @@ pub fn utilize<'a, 'b, 'c, 'info>(
     ctx: CpiContext<'a, 'b, 'c, 'info, Utilize<'info>>,
     number_of_uses: u64,
 ) -> Result<()> {
+    // The record and burner are only read by the metadata program for delegated use authorities.
+    let delegated = ctx.accounts.use_authority.key() != ctx.accounts.owner.key();
     let ix = instruction::utilize(
         mpl_token_metadata::ID,
         ctx.accounts.metadata.key(),
         ctx.accounts.token_account.key(),
         ctx.accounts.mint.key(),
-        Some(ctx.accounts.use_authority_record_pda.key()),
+        delegated.then(|| ctx.accounts.use_authority_record_pda.key()),
         ctx.accounts.use_authority.key(),
         ctx.accounts.owner.key(),
-        Some(ctx.accounts.burner.key()),
+        delegated.then(|| ctx.accounts.burner.key()),
         number_of_uses,
     );
 
-    solana_program::program::invoke_signed(
-        &ix,
-        &[
-            ctx.accounts.metadata,
-            ctx.accounts.token_account,
-            ctx.accounts.mint,
-            ctx.accounts.use_authority,
-            ctx.accounts.owner,
-            ctx.accounts.token_program.to_account_info(),
-            ctx.accounts.associated_token.to_account_info(),
-            ctx.accounts.system_program.to_account_info(),
-            ctx.accounts.rent.to_account_info(),
-        ],
-        ctx.signer_seeds,
-    )
-    .map_err(Into::into)
+    let mut account_infos = vec![
+        ctx.accounts.metadata,
+        ctx.accounts.token_account,
+        ctx.accounts.mint,
+        ctx.accounts.use_authority,
+        ctx.accounts.owner,
+        ctx.accounts.token_program.to_account_info(),
+        ctx.accounts.associated_token.to_account_info(),
+        ctx.accounts.system_program.to_account_info(),
+        ctx.accounts.rent.to_account_info(),
+    ];
+    if delegated {
+        account_infos.push(ctx.accounts.use_authority_record_pda);
+        account_infos.push(ctx.accounts.burner);
+    }
+    solana_program::program::invoke_signed(&ix, &account_infos, ctx.signer_seeds)
+        .map_err(Into::into)
 }
 
 #[derive(Accounts)]
 pub struct Utilize<'info> {
+    /// CHECK: metadata PDA, validated by the metadata program
+    #[account(mut)]
     pub metadata: AccountInfo<'info>,
+    /// CHECK: token account holding the NFT
+    #[account(mut)]
     pub token_account: AccountInfo<'info>,
+    /// CHECK: mint of the NFT, validated by the metadata program
+    #[account(mut)]
     pub mint: AccountInfo<'info>,
+    /// CHECK: use authority record PDA, only read for delegated use authorities
+    #[account(mut)]
     pub use_authority_record_pda: AccountInfo<'info>,
+    /// CHECK: owner or delegated use authority of the NFT
+    #[account(mut, signer)]
     pub use_authority: AccountInfo<'info>,
+    /// CHECK: owner of the NFT
     pub owner: AccountInfo<'info>,
+    /// CHECK: burner PDA of the metadata program
     pub burner: AccountInfo<'info>,
     pub associated_token: Program<'info, AssociatedToken>,
     pub system_program: Program<'info, System>,
@@ -683,13 +974,25 @@ pub fn approve_use_authority<'a, 'b, 'c, 'info>(
 
 #[derive(Accounts)]
 pub struct ApproveUseAuthority<'info> {
+    /// CHECK: use authority record PDA of the user
+    #[account(mut)]
     pub use_authority_record: AccountInfo<'info>,
+    /// CHECK: use authority being approved or revoked
     pub user: AccountInfo<'info>,
+    /// CHECK: owner of the NFT
+    #[account(mut, signer)]
     pub owner: AccountInfo<'info>,
+    /// CHECK: pays for any accounts created
+    #[account(mut, signer)]
     pub payer: AccountInfo<'info>,
+    /// CHECK: token account of the owner holding the NFT
+    #[account(mut)]
     pub owner_token_account: AccountInfo<'info>,
+    /// CHECK: metadata PDA, validated by the metadata program
     pub metadata: AccountInfo<'info>,
+    /// CHECK: mint of the NFT, validated by the metadata program
     pub mint: AccountInfo<'info>,
+    /// CHECK: burner PDA of the metadata program
     pub burner: AccountInfo<'info>,
     pub system_program: Program<'info, System>,
     pub token_program: Program<'info, Token>,
@@ -729,11 +1032,20 @@ pub fn revoke_use_authority<'a, 'b, 'c, 'info>(
 
 #[derive(Accounts)]
 pub struct RevokeUseAuthority<'info> {
+    /// CHECK: use authority record PDA of the user
+    #[account(mut)]
     pub use_authority_record: AccountInfo<'info>,
+    /// CHECK: use authority being approved or revoked
     pub user: AccountInfo<'info>,
+    /// CHECK: owner of the NFT
+    #[account(mut, signer)]
     pub owner: AccountInfo<'info>,
+    /// CHECK: token account of the owner holding the NFT
+    #[account(mut)]
     pub owner_token_account: AccountInfo<'info>,
+    /// CHECK: metadata PDA, validated by the metadata program
     pub metadata: AccountInfo<'info>,
+    /// CHECK: mint of the NFT, validated by the metadata program
     pub mint: AccountInfo<'info>,
     pub system_program: Program<'info, System>,
     pub token_program: Program<'info, Token>,
@@ -772,14 +1084,22 @@ pub fn approve_collection_authority<'a, 'b, 'c, 'info>(
 
 #[derive(Accounts)]
 pub struct ApproveCollectionAuthority<'info> {
+    /// CHECK: collection authority record PDA of the delegate
+    #[account(mut)]
     pub collection_authority_record: AccountInfo<'info>,
+    /// CHECK: collection delegate being approved
     pub new_collection_authority: AccountInfo<'info>,
+    /// CHECK: update authority of the collection NFT
+    #[account(mut, signer)]
     pub update_authority: AccountInfo<'info>,
+    /// CHECK: pays for any accounts created
+    #[account(mut, signer)]
     pub payer: AccountInfo<'info>,
+    /// CHECK: metadata PDA of the collection NFT
     pub metadata: AccountInfo<'info>,
+    /// CHECK: mint of the collection NFT
     pub mint: AccountInfo<'info>,
     pub system_program: Program<'info, System>,
-    pub token_program: Program<'info, Token>,
     pub rent: Sysvar<'info, Rent>,
 }
 
@@ -811,13 +1131,19 @@ pub fn revoke_collection_authority<'a, 'b, 'c, 'info>(
 
 #[derive(Accounts)]
 pub struct RevokeCollectionAuthority<'info> {
+    /// CHECK: collection authority record PDA of the delegate
+    #[account(mut)]
     pub collection_authority_record: AccountInfo<'info>,
+    /// CHECK: collection delegate being revoked
     pub delegate_authority: AccountInfo<'info>,
+    /// CHECK: update authority of the collection or the delegate itself
+    #[account(mut, signer)]
     pub update_authority: AccountInfo<'info>,
+    /// CHECK: metadata PDA of the collection NFT
     pub metadata: AccountInfo<'info>,
+    /// CHECK: mint of the collection NFT
     pub mint: AccountInfo<'info>,
     pub system_program: Program<'info, System>,
-    pub token_program: Program<'info, Token>,
     pub rent: Sysvar<'info, Rent>,
 }
 
@@ -827,13 +1153,13 @@ pub fn set_and_verify_collection<'a, 'b, 'c, 'info>(
     let ix = instruction::set_and_verify_collection(
         mpl_token_metadata::ID,
         ctx.accounts.metadata.key(),
-        ctx.accounts.collection.key(),
+        ctx.accounts.collection_authority.key(),
         ctx.accounts.payer.key(),
         ctx.accounts.update_authority.key(),
         ctx.accounts.collection_mint.key(),
         ctx.accounts.collection.key(),
         ctx.accounts.collection_master_edition_account.key(),
-        Some(ctx.accounts.collection_authority_record.key()),
+        approved_collection_authority_record(&ctx.accounts.collection_authority_record),
     );
 
     solana_program::program::invoke_signed(
@@ -846,6 +1172,7 @@ pub fn set_and_verify_collection<'a, 'b, 'c, 'info>(
             ctx.accounts.collection_mint,
             ctx.accounts.collection,
             ctx.accounts.collection_master_edition_account,
+            ctx.accounts.collection_authority_record,
         ],
         ctx.signer_seeds,
     )
@@ -854,13 +1181,24 @@ pub fn set_and_verify_collection<'a, 'b, 'c, 'info>(
 
 #[derive(Accounts)]
 pub struct SetAndVerifyCollection<'info> {
+    /// CHECK: metadata PDA, validated by the metadata program
+    #[account(mut)]
     pub metadata: AccountInfo<'info>,
+    /// CHECK: update authority or approved delegate of the collection
+    #[account(mut, signer)]
     pub collection_authority: AccountInfo<'info>,
+    /// CHECK: pays for any accounts created
+    #[account(mut, signer)]
     pub payer: AccountInfo<'info>,
+    /// CHECK: update authority of the item metadata
     pub update_authority: AccountInfo<'info>,
+    /// CHECK: mint of the collection NFT
     pub collection_mint: AccountInfo<'info>,
+    /// CHECK: metadata PDA of the collection NFT
     pub collection: AccountInfo<'info>,
+    /// CHECK: master edition PDA of the collection NFT
     pub collection_master_edition_account: AccountInfo<'info>,
+    /// CHECK: collection authority record PDA of the delegate
     pub collection_authority_record: AccountInfo<'info>,
 }
 
@@ -882,6 +1220,7 @@ pub fn freeze_delegated_account<'a, 'b, 'c, 'info>(
             ctx.accounts.token_account,
             ctx.accounts.edition,
             ctx.accounts.mint,
+            ctx.accounts.token_program.to_account_info(),
         ],
         ctx.signer_seeds,
     )
@@ -890,11 +1229,19 @@ pub fn freeze_delegated_account<'a, 'b, 'c, 'info>(
 
 #[derive(Accounts)]
 pub struct FreezeDelegatedAccount<'info> {
+    /// CHECK: delegate of the token account
+    #[account(mut, signer)]
     pub delegate: AccountInfo<'info>,
+    /// CHECK: token account holding the NFT
+    #[account(mut)]
     pub token_account: AccountInfo<'info>,
+    /// CHECK: edition PDA of the mint
     pub edition: AccountInfo<'info>,
+    /// CHECK: unused by the metadata program
     pub update_authority: AccountInfo<'info>,
+    /// CHECK: mint of the NFT, validated by the metadata program
     pub mint: AccountInfo<'info>,
+    pub token_program: Program<'info, Token>,
 }
 
 pub fn thaw_delegated_account<'a, 'b, 'c, 'info>(
@@ -915,6 +1262,7 @@ pub fn thaw_delegated_account<'a, 'b, 'c, 'info>(
             ctx.accounts.token_account,
             ctx.accounts.edition,
             ctx.accounts.mint,
+            ctx.accounts.token_program.to_account_info(),
         ],
         ctx.signer_seeds,
     )
@@ -923,9 +1271,65 @@ pub fn thaw_delegated_account<'a, 'b, 'c, 'info>(
 
 #[derive(Accounts)]
 pub struct ThawDelegatedAccount<'info> {
+    /// CHECK: delegate of the token account
+    #[account(mut, signer)]
     pub delegate: AccountInfo<'info>,
+    /// CHECK: token account holding the NFT
+    #[account(mut)]
     pub token_account: AccountInfo<'info>,
+    /// CHECK: edition PDA of the mint
     pub edition: AccountInfo<'info>,
+    /// CHECK: unused by the metadata program
+    pub update_authority: AccountInfo<'info>,
+    /// CHECK: mint of the NFT, validated by the metadata program
+    pub mint: AccountInfo<'info>,
+    pub token_program: Program<'info, Token>,
+}
+
+/// Sets the token standard of metadata created before the field existed, as determined by the
+/// metadata program from the mint and edition.
+///
+/// Pass the metadata program as `edition` for mints without an edition.
+pub fn set_token_standard<'a, 'b, 'c, 'info>(
+    ctx: CpiContext<'a, 'b, 'c, 'info, SetTokenStandard<'info>>,
+) -> Result<()> {
+    let has_edition = ctx.accounts.edition.key() != mpl_token_metadata::ID;
+    let ix = instruction::set_token_standard(
+        mpl_token_metadata::ID,
+        ctx.accounts.metadata.key(),
+        ctx.accounts.update_authority.key(),
+        ctx.accounts.mint.key(),
+        has_edition.then(|| ctx.accounts.edition.key()),
+    );
+
+    let mut account_infos = vec![
+        ctx.accounts.metadata,
+        ctx.accounts.update_authority,
+        ctx.accounts.mint,
+    ];
+    if has_edition {
+        account_infos.push(ctx.accounts.edition);
+    }
+    solana_program::program::invoke_signed(&ix, &account_infos, ctx.signer_seeds)
+        .map_err(Into::into)
+}
+
+#[derive(Accounts)]
+pub struct SetTokenStandard<'info> {
+    /// CHECK: metadata PDA of the mint, validated by the metadata program
+    #[account(mut)]
+    pub metadata: AccountInfo<'info>,
+    /// CHECK: update authority of the metadata
+    #[account(mut, signer)]
     pub update_authority: AccountInfo<'info>,
+    /// CHECK: mint of the metadata, validated by the metadata program
     pub mint: AccountInfo<'info>,
+    /// CHECK: master or print edition PDA of the mint, or the metadata program if it has none
+    pub edition: AccountInfo<'info>,
+}
+
+/// The collection authority record to hand to the metadata program, which only accepts one
+/// that has been approved; update authorities verify without a record.
+fn approved_collection_authority_record(record: &AccountInfo) -> Option<Pubkey> {
+    (!record.data_is_empty()).then(|| record.key())
 }
@@ -0,0 +1,80 @@
+use anchor_lang::prelude::*;
+use anchor_lang::system_program;
+use anchor_spl::associated_token;
+use anchor_spl::token::{self, Mint};
+
+/// Accounts needed to create a fresh NFT mint holding a single token.
+pub(crate) struct NftMint<'info> {
+    pub payer: AccountInfo<'info>,
+    pub mint: AccountInfo<'info>,
+    pub mint_authority: AccountInfo<'info>,
+    pub token_account: AccountInfo<'info>,
+    pub token_account_owner: AccountInfo<'info>,
+    pub associated_token_program: AccountInfo<'info>,
+    pub system_program: AccountInfo<'info>,
+    pub token_program: AccountInfo<'info>,
+    pub rent: AccountInfo<'info>,
+}
+
+/// Creates and initializes a zero-decimal mint, creates the owner's associated token account
+/// and mints one token into it.
+///
+/// `mint_authority` is also set as freeze authority, which the metadata program requires before
+/// it hands both authorities to the edition PDA.
+pub(crate) fn create_nft_mint<'info>(
+    accounts: NftMint<'info>,
+    signer_seeds: &[&[&[u8]]],
+) -> Result<()> {
+    let rent = Rent::from_account_info(&accounts.rent)?;
+    system_program::create_account(
+        CpiContext::new_with_signer(
+            accounts.system_program.clone(),
+            system_program::CreateAccount {
+                from: accounts.payer.clone(),
+                to: accounts.mint.clone(),
+            },
+            signer_seeds,
+        ),
+        rent.minimum_balance(Mint::LEN),
+        Mint::LEN as u64,
+        &token::ID,
+    )?;
+    token::initialize_mint(
+        CpiContext::new_with_signer(
+            accounts.token_program.clone(),
+            token::InitializeMint {
+                mint: accounts.mint.clone(),
+                rent: accounts.rent.clone(),
+            },
+            signer_seeds,
+        ),
+        0,
+        accounts.mint_authority.key,
+        Some(accounts.mint_authority.key),
+    )?;
+    associated_token::create(CpiContext::new_with_signer(
+        accounts.associated_token_program,
+        associated_token::Create {
+            payer: accounts.payer,
+            associated_token: accounts.token_account.clone(),
+            authority: accounts.token_account_owner,
+            mint: accounts.mint.clone(),
+            system_program: accounts.system_program,
+            token_program: accounts.token_program.clone(),
+            rent: accounts.rent,
+        },
+        signer_seeds,
+    ))?;
+    token::mint_to(
+        CpiContext::new_with_signer(
+            accounts.token_program,
+            token::MintTo {
+                mint: accounts.mint,
+                to: accounts.token_account,
+                authority: accounts.mint_authority,
+            },
+            signer_seeds,
+        ),
+        1,
+    )
+}
@@ -0,0 +1,1253 @@
+use anchor_lang::prelude::*;
+use anchor_lang::solana_program;
+use anchor_lang::solana_program::instruction::{AccountMeta, Instruction};
+use anchor_lang::solana_program::sysvar;
+use anchor_spl::{associated_token::AssociatedToken, token::Token};
+
+use crate::assertions::assert_metadata_for_mint;
+use crate::error::MplCpiError;
+use crate::reader::MetadataReader;
+use crate::types::{
+    Collection, CollectionDetails, Creator, ProgrammableConfig, TokenStandard, Uses,
+};
+use crate::{FreezeDelegatedAccount, ThawDelegatedAccount};
+
+// Wrappers for the unified instructions of the metadata program that programmable NFTs require.
+// `mpl_token_metadata` 1.4.3 predates them, so the instructions are built here by hand.
+//
+// Optional accounts are left out by passing the metadata program in their place, which is how
+// the metadata program itself marks them as absent. Optional accounts are not declared `mut` in
+// the accounts structs for that reason, but are passed on as writable when present.
+// Authorization data for rule sets is not supported and always sent as `None`.
+
+const CREATE: u8 = 42;
+const MINT: u8 = 43;
+const DELEGATE: u8 = 44;
+const REVOKE: u8 = 45;
+const LOCK: u8 = 46;
+const UNLOCK: u8 = 47;
+const TRANSFER: u8 = 49;
+
+const TOKEN_RECORD: &str = "token_record";
+const COLLECTION_DELEGATE: &str = "collection_delegate";
+const DATA_DELEGATE: &str = "data_delegate";
+const PROGRAMMABLE_CONFIG_DELEGATE: &str = "programmable_config_delegate";
+
+/// Data of a new asset, as passed to [`create_v1`].
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Debug, PartialEq, Eq)]
+pub struct AssetData {
+    pub name: String,
+    pub symbol: String,
+    pub uri: String,
+    pub seller_fee_basis_points: u16,
+    pub creators: Option<Vec<Creator>>,
+    pub primary_sale_happened: bool,
+    pub is_mutable: bool,
+    pub token_standard: TokenStandard,
+    pub collection: Option<Collection>,
+    pub uses: Option<Uses>,
+    pub collection_details: Option<CollectionDetails>,
+    /// Authorization rule set enforced on programmable NFTs.
+    pub rule_set: Option<Pubkey>,
+}
+
+/// Number of prints the master edition created by [`create_v1`] allows.
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, Debug, PartialEq, Eq)]
+pub enum PrintSupply {
+    Zero,
+    Limited(u64),
+    Unlimited,
+}
+
+#[derive(AnchorSerialize)]
+enum CreateArgs {
+    V1 {
+        asset_data: AssetData,
+        decimals: Option<u8>,
+        print_supply: Option<PrintSupply>,
+    },
+}
+
+#[derive(AnchorSerialize)]
+enum MintArgs {
+    V1 {
+        amount: u64,
+        authorization_data: Option<()>,
+    },
+}
+
+#[derive(AnchorSerialize)]
+enum TransferArgs {
+    V1 {
+        amount: u64,
+        authorization_data: Option<()>,
+    },
+}
+
+#[derive(AnchorSerialize)]
+enum LockArgs {
+    V1 { authorization_data: Option<()> },
+}
+
+#[derive(AnchorSerialize)]
+enum UnlockArgs {
+    V1 { authorization_data: Option<()> },
+}
+
+#[derive(AnchorSerialize)]
+enum DelegateArgs {
+    CollectionV1 {
+        authorization_data: Option<()>,
+    },
+    SaleV1 {
+        amount: u64,
+        authorization_data: Option<()>,
+    },
+    TransferV1 {
+        amount: u64,
+        authorization_data: Option<()>,
+    },
+    DataV1 {
+        authorization_data: Option<()>,
+    },
+    UtilityV1 {
+        amount: u64,
+        authorization_data: Option<()>,
+    },
+    StakingV1 {
+        amount: u64,
+        authorization_data: Option<()>,
+    },
+    StandardV1 {
+        amount: u64,
+    },
+    LockedTransferV1 {
+        amount: u64,
+        locked_address: Pubkey,
+        authorization_data: Option<()>,
+    },
+    ProgrammableConfigV1 {
+        authorization_data: Option<()>,
+    },
+}
+
+#[derive(AnchorSerialize)]
+enum RevokeArgs {
+    CollectionV1,
+    SaleV1,
+    TransferV1,
+    DataV1,
+    UtilityV1,
+    StakingV1,
+    StandardV1,
+    LockedTransferV1,
+    ProgrammableConfigV1,
+}
+
+/// Role of a delegate approved with [`delegate_v1`].
+///
+/// `Collection`, `Data` and `ProgrammableConfig` delegate authority over the metadata and are
+/// recorded in a metadata delegate record. The other roles delegate a token account: `Standard`
+/// for assets that are not programmable, the rest for programmable NFTs, whose token record
+/// holds the delegate.
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, Debug, PartialEq, Eq)]
+pub enum DelegateRole {
+    Collection,
+    Data,
+    ProgrammableConfig,
+    Sale,
+    Transfer,
+    Utility,
+    Staking,
+    Standard,
+    /// Transfer delegate that can only transfer to `locked_address`.
+    LockedTransfer {
+        locked_address: Pubkey,
+    },
+}
+
+impl DelegateRole {
+    /// Whether the role delegates authority over the metadata rather than a token account.
+    pub fn is_metadata_role(&self) -> bool {
+        matches!(
+            self,
+            DelegateRole::Collection | DelegateRole::Data | DelegateRole::ProgrammableConfig
+        )
+    }
+
+    /// The record the metadata program keeps for `delegate` in this role: the metadata delegate
+    /// record for metadata roles, the token record of `token` for programmable token roles, and
+    /// none for `Standard`.
+    pub fn find_record_account(
+        &self,
+        mint: &Pubkey,
+        update_authority: &Pubkey,
+        delegate: &Pubkey,
+        token: &Pubkey,
+    ) -> Option<(Pubkey, u8)> {
+        let seed = match self {
+            DelegateRole::Collection => COLLECTION_DELEGATE,
+            DelegateRole::Data => DATA_DELEGATE,
+            DelegateRole::ProgrammableConfig => PROGRAMMABLE_CONFIG_DELEGATE,
+            DelegateRole::Standard => return None,
+            _ => return Some(find_token_record_account(mint, token)),
+        };
+        Some(find_metadata_delegate_record_account(
+            mint,
+            seed,
+            update_authority,
+            delegate,
+        ))
+    }
+
+    fn delegate_args(self, amount: u64) -> DelegateArgs {
+        let authorization_data = None;
+        match self {
+            DelegateRole::Collection => DelegateArgs::CollectionV1 { authorization_data },
+            DelegateRole::Data => DelegateArgs::DataV1 { authorization_data },
+            DelegateRole::ProgrammableConfig => {
+                DelegateArgs::ProgrammableConfigV1 { authorization_data }
+            }
+            DelegateRole::Sale => DelegateArgs::SaleV1 {
+                amount,
+                authorization_data,
+            },
+            DelegateRole::Transfer => DelegateArgs::TransferV1 {
+                amount,
+                authorization_data,
+            },
+            DelegateRole::Utility => DelegateArgs::UtilityV1 {
+                amount,
+                authorization_data,
+            },
+            DelegateRole::Staking => DelegateArgs::StakingV1 {
+                amount,
+                authorization_data,
+            },
+            DelegateRole::Standard => DelegateArgs::StandardV1 { amount },
+            DelegateRole::LockedTransfer { locked_address } => DelegateArgs::LockedTransferV1 {
+                amount,
+                locked_address,
+                authorization_data,
+            },
+        }
+    }
+
+    fn revoke_args(self) -> RevokeArgs {
+        match self {
+            DelegateRole::Collection => RevokeArgs::CollectionV1,
+            DelegateRole::Data => RevokeArgs::DataV1,
+            DelegateRole::ProgrammableConfig => RevokeArgs::ProgrammableConfigV1,
+            DelegateRole::Sale => RevokeArgs::SaleV1,
+            DelegateRole::Transfer => RevokeArgs::TransferV1,
+            DelegateRole::Utility => RevokeArgs::UtilityV1,
+            DelegateRole::Staking => RevokeArgs::StakingV1,
+            DelegateRole::Standard => RevokeArgs::StandardV1,
+            DelegateRole::LockedTransfer { .. } => RevokeArgs::LockedTransferV1,
+        }
+    }
+}
+
+/// Metadata delegate record PDA of `delegate`, approved by `update_authority` for `mint` in the
+/// role identified by `role_seed`.
+fn find_metadata_delegate_record_account(
+    mint: &Pubkey,
+    role_seed: &str,
+    update_authority: &Pubkey,
+    delegate: &Pubkey,
+) -> (Pubkey, u8) {
+    Pubkey::find_program_address(
+        &[
+            crate::PREFIX.as_bytes(),
+            mpl_token_metadata::ID.as_ref(),
+            mint.as_ref(),
+            role_seed.as_bytes(),
+            update_authority.as_ref(),
+            delegate.as_ref(),
+        ],
+        &mpl_token_metadata::ID,
+    )
+}
+
+/// Token record PDA that tracks the state of the programmable NFT `mint` held in `token`.
+pub fn find_token_record_account(mint: &Pubkey, token: &Pubkey) -> (Pubkey, u8) {
+    Pubkey::find_program_address(
+        &[
+            crate::PREFIX.as_bytes(),
+            mpl_token_metadata::ID.as_ref(),
+            mint.as_ref(),
+            TOKEN_RECORD.as_bytes(),
+            token.as_ref(),
+        ],
+        &mpl_token_metadata::ID,
+    )
+}
+
+/// Creates the metadata and, for non-fungible standards, the master edition of `mint`,
+/// initializing the mint first if it does not exist yet, in which case it must sign.
+pub fn create_v1<'a, 'b, 'c, 'info>(
+    ctx: CpiContext<'a, 'b, 'c, 'info, CreateV1<'info>>,
+    asset_data: AssetData,
+    decimals: Option<u8>,
+    print_supply: Option<PrintSupply>,
+    update_authority_is_signer: bool,
+) -> Result<()> {
+    let accounts = ctx.accounts;
+    let ix = Instruction {
+        program_id: mpl_token_metadata::ID,
+        accounts: vec![
+            AccountMeta::new(accounts.metadata.key(), false),
+            optional_meta(&accounts.master_edition, true),
+            AccountMeta::new(accounts.mint.key(), accounts.mint.data_is_empty()),
+            AccountMeta::new_readonly(accounts.authority.key(), true),
+            AccountMeta::new(accounts.payer.key(), true),
+            AccountMeta::new_readonly(accounts.update_authority.key(), update_authority_is_signer),
+            AccountMeta::new_readonly(accounts.system_program.key(), false),
+            AccountMeta::new_readonly(accounts.sysvar_instructions.key(), false),
+            AccountMeta::new_readonly(accounts.spl_token_program.key(), false),
+        ],
+        data: instruction_data(
+            CREATE,
+            CreateArgs::V1 {
+                asset_data,
+                decimals,
+                print_supply,
+            },
+        ),
+    };
+    solana_program::program::invoke_signed(
+        &ix,
+        &[
+            accounts.metadata,
+            accounts.master_edition,
+            accounts.mint,
+            accounts.authority,
+            accounts.payer,
+            accounts.update_authority,
+            accounts.system_program.to_account_info(),
+            accounts.sysvar_instructions,
+            accounts.spl_token_program.to_account_info(),
+            ctx.program,
+        ],
+        ctx.signer_seeds,
+    )
+    .map_err(Into::into)
+}
+
+#[derive(Accounts)]
+pub struct CreateV1<'info> {
+    /// CHECK: metadata PDA of the mint, validated by the metadata program
+    #[account(mut)]
+    pub metadata: AccountInfo<'info>,
+    /// CHECK: master edition PDA of the mint, or the metadata program for fungible standards
+    pub master_edition: AccountInfo<'info>,
+    /// CHECK: mint of the asset, signs if it still has to be initialized
+    #[account(mut)]
+    pub mint: AccountInfo<'info>,
+    /// CHECK: mint authority of the mint
+    #[account(signer)]
+    pub authority: AccountInfo<'info>,
+    /// CHECK: pays for the accounts created
+    #[account(mut, signer)]
+    pub payer: AccountInfo<'info>,
+    /// CHECK: update authority to set, signs when `update_authority_is_signer`
+    pub update_authority: AccountInfo<'info>,
+    pub system_program: Program<'info, System>,
+    /// CHECK: instructions sysvar
+    #[account(address = sysvar::instructions::ID)]
+    pub sysvar_instructions: AccountInfo<'info>,
+    pub spl_token_program: Program<'info, Token>,
+}
+
+/// Mints `amount` tokens of an asset created with [`create_v1`] into `token`, creating the
+/// associated token account of `token_owner` and, for programmable NFTs, its token record if
+/// needed.
+pub fn mint_v1<'a, 'b, 'c, 'info>(
+    ctx: CpiContext<'a, 'b, 'c, 'info, MintV1<'info>>,
+    amount: u64,
+) -> Result<()> {
+    let accounts = ctx.accounts;
+    let ix = Instruction {
+        program_id: mpl_token_metadata::ID,
+        accounts: vec![
+            AccountMeta::new(accounts.token.key(), false),
+            optional_meta(&accounts.token_owner, false),
+            AccountMeta::new_readonly(accounts.metadata.key(), false),
+            optional_meta(&accounts.master_edition, false),
+            optional_meta(&accounts.token_record, true),
+            AccountMeta::new(accounts.mint.key(), false),
+            AccountMeta::new_readonly(accounts.authority.key(), true),
+            optional_meta(&accounts.delegate_record, false),
+            AccountMeta::new(accounts.payer.key(), true),
+            AccountMeta::new_readonly(accounts.system_program.key(), false),
+            AccountMeta::new_readonly(accounts.sysvar_instructions.key(), false),
+            AccountMeta::new_readonly(accounts.spl_token_program.key(), false),
+            AccountMeta::new_readonly(accounts.spl_ata_program.key(), false),
+            optional_meta(&accounts.authorization_rules_program, false),
+            optional_meta(&accounts.authorization_rules, false),
+        ],
+        data: instruction_data(
+            MINT,
+            MintArgs::V1 {
+                amount,
+                authorization_data: None,
+            },
+        ),
+    };
+    solana_program::program::invoke_signed(
+        &ix,
+        &[
+            accounts.token,
+            accounts.token_owner,
+            accounts.metadata,
+            accounts.master_edition,
+            accounts.token_record,
+            accounts.mint,
+            accounts.authority,
+            accounts.delegate_record,
+            accounts.payer,
+            accounts.system_program.to_account_info(),
+            accounts.sysvar_instructions,
+            accounts.spl_token_program.to_account_info(),
+            accounts.spl_ata_program.to_account_info(),
+            accounts.authorization_rules_program,
+            accounts.authorization_rules,
+            ctx.program,
+        ],
+        ctx.signer_seeds,
+    )
+    .map_err(Into::into)
+}
+
+#[derive(Accounts)]
+pub struct MintV1<'info> {
+    /// CHECK: token account to mint into, created by the metadata program if it is an ATA
+    #[account(mut)]
+    pub token: AccountInfo<'info>,
+    /// CHECK: owner of `token`, or the metadata program if `token` already exists
+    pub token_owner: AccountInfo<'info>,
+    /// CHECK: metadata PDA of the mint, validated by the metadata program
+    pub metadata: AccountInfo<'info>,
+    /// CHECK: master edition PDA of the mint, or the metadata program for fungible standards
+    pub master_edition: AccountInfo<'info>,
+    /// CHECK: token record PDA of `token` for programmable NFTs (writable), or the metadata program
+    pub token_record: AccountInfo<'info>,
+    /// CHECK: mint of the asset, validated by the metadata program
+    #[account(mut)]
+    pub mint: AccountInfo<'info>,
+    /// CHECK: update authority or mint authority of the asset
+    #[account(signer)]
+    pub authority: AccountInfo<'info>,
+    /// CHECK: metadata delegate record of `authority`, or the metadata program
+    pub delegate_record: AccountInfo<'info>,
+    /// CHECK: pays for the accounts created
+    #[account(mut, signer)]
+    pub payer: AccountInfo<'info>,
+    pub system_program: Program<'info, System>,
+    /// CHECK: instructions sysvar
+    #[account(address = sysvar::instructions::ID)]
+    pub sysvar_instructions: AccountInfo<'info>,
+    pub spl_token_program: Program<'info, Token>,
+    pub spl_ata_program: Program<'info, AssociatedToken>,
+    /// CHECK: authorization rules program, or the metadata program
+    pub authorization_rules_program: AccountInfo<'info>,
+    /// CHECK: rule set of the asset, or the metadata program
+    pub authorization_rules: AccountInfo<'info>,
+}
+
+/// Transfers `amount` tokens from `token` to `destination`, creating the associated token
+/// account of `destination_owner` if needed.
+pub fn transfer_v1<'a, 'b, 'c, 'info>(
+    ctx: CpiContext<'a, 'b, 'c, 'info, TransferV1<'info>>,
+    amount: u64,
+) -> Result<()> {
+    let accounts = ctx.accounts;
+    let ix = Instruction {
+        program_id: mpl_token_metadata::ID,
+        accounts: vec![
+            AccountMeta::new(accounts.token.key(), false),
+            AccountMeta::new_readonly(accounts.token_owner.key(), false),
+            AccountMeta::new(accounts.destination.key(), false),
+            AccountMeta::new_readonly(accounts.destination_owner.key(), false),
+            AccountMeta::new_readonly(accounts.mint.key(), false),
+            AccountMeta::new(accounts.metadata.key(), false),
+            optional_meta(&accounts.edition, false),
+            optional_meta(&accounts.owner_token_record, true),
+            optional_meta(&accounts.destination_token_record, true),
+            AccountMeta::new_readonly(accounts.authority.key(), true),
+            AccountMeta::new(accounts.payer.key(), true),
+            AccountMeta::new_readonly(accounts.system_program.key(), false),
+            AccountMeta::new_readonly(accounts.sysvar_instructions.key(), false),
+            AccountMeta::new_readonly(accounts.spl_token_program.key(), false),
+            AccountMeta::new_readonly(accounts.spl_ata_program.key(), false),
+            optional_meta(&accounts.authorization_rules_program, false),
+            optional_meta(&accounts.authorization_rules, false),
+        ],
+        data: instruction_data(
+            TRANSFER,
+            TransferArgs::V1 {
+                amount,
+                authorization_data: None,
+            },
+        ),
+    };
+    solana_program::program::invoke_signed(
+        &ix,
+        &[
+            accounts.token,
+            accounts.token_owner,
+            accounts.destination,
+            accounts.destination_owner,
+            accounts.mint,
+            accounts.metadata,
+            accounts.edition,
+            accounts.owner_token_record,
+            accounts.destination_token_record,
+            accounts.authority,
+            accounts.payer,
+            accounts.system_program.to_account_info(),
+            accounts.sysvar_instructions,
+            accounts.spl_token_program.to_account_info(),
+            accounts.spl_ata_program.to_account_info(),
+            accounts.authorization_rules_program,
+            accounts.authorization_rules,
+            ctx.program,
+        ],
+        ctx.signer_seeds,
+    )
+    .map_err(Into::into)
+}
+
+#[derive(Accounts)]
+pub struct TransferV1<'info> {
+    /// CHECK: token account holding the asset
+    #[account(mut)]
+    pub token: AccountInfo<'info>,
+    /// CHECK: owner of `token`
+    pub token_owner: AccountInfo<'info>,
+    /// CHECK: token account to transfer to, created by the metadata program if it is an ATA
+    #[account(mut)]
+    pub destination: AccountInfo<'info>,
+    /// CHECK: owner of `destination`
+    pub destination_owner: AccountInfo<'info>,
+    /// CHECK: mint of the asset, validated by the metadata program
+    pub mint: AccountInfo<'info>,
+    /// CHECK: metadata PDA of the mint, validated by the metadata program
+    #[account(mut)]
+    pub metadata: AccountInfo<'info>,
+    /// CHECK: edition PDA of the mint, or the metadata program for fungible standards
+    pub edition: AccountInfo<'info>,
+    /// CHECK: token record PDA of `token` for programmable NFTs (writable), or the metadata program
+    pub owner_token_record: AccountInfo<'info>,
+    /// CHECK: token record PDA of `destination` for programmable NFTs (writable), or the metadata
+    /// program
+    pub destination_token_record: AccountInfo<'info>,
+    /// CHECK: owner of `token` or its transfer delegate
+    #[account(signer)]
+    pub authority: AccountInfo<'info>,
+    /// CHECK: pays for the accounts created
+    #[account(mut, signer)]
+    pub payer: AccountInfo<'info>,
+    pub system_program: Program<'info, System>,
+    /// CHECK: instructions sysvar
+    #[account(address = sysvar::instructions::ID)]
+    pub sysvar_instructions: AccountInfo<'info>,
+    pub spl_token_program: Program<'info, Token>,
+    pub spl_ata_program: Program<'info, AssociatedToken>,
+    /// CHECK: authorization rules program, or the metadata program
+    pub authorization_rules_program: AccountInfo<'info>,
+    /// CHECK: rule set of the asset, or the metadata program
+    pub authorization_rules: AccountInfo<'info>,
+}
+
+/// Approves `delegate` in `role`; `amount` is the number of tokens delegated and is ignored by
+/// metadata roles.
+pub fn delegate_v1<'a, 'b, 'c, 'info>(
+    ctx: CpiContext<'a, 'b, 'c, 'info, DelegateV1<'info>>,
+    role: DelegateRole,
+    amount: u64,
+) -> Result<()> {
+    let accounts = ctx.accounts;
+    let ix = Instruction {
+        program_id: mpl_token_metadata::ID,
+        accounts: vec![
+            optional_meta(&accounts.delegate_record, true),
+            AccountMeta::new_readonly(accounts.delegate.key(), false),
+            AccountMeta::new(accounts.metadata.key(), false),
+            optional_meta(&accounts.master_edition, false),
+            optional_meta(&accounts.token_record, true),
+            AccountMeta::new_readonly(accounts.mint.key(), false),
+            optional_meta(&accounts.token, true),
+            AccountMeta::new_readonly(accounts.authority.key(), true),
+            AccountMeta::new(accounts.payer.key(), true),
+            AccountMeta::new_readonly(accounts.system_program.key(), false),
+            AccountMeta::new_readonly(accounts.sysvar_instructions.key(), false),
+            optional_meta(&accounts.spl_token_program, false),
+            optional_meta(&accounts.authorization_rules_program, false),
+            optional_meta(&accounts.authorization_rules, false),
+        ],
+        data: instruction_data(DELEGATE, role.delegate_args(amount)),
+    };
+    solana_program::program::invoke_signed(
+        &ix,
+        &[
+            accounts.delegate_record,
+            accounts.delegate,
+            accounts.metadata,
+            accounts.master_edition,
+            accounts.token_record,
+            accounts.mint,
+            accounts.token,
+            accounts.authority,
+            accounts.payer,
+            accounts.system_program.to_account_info(),
+            accounts.sysvar_instructions,
+            accounts.spl_token_program,
+            accounts.authorization_rules_program,
+            accounts.authorization_rules,
+            ctx.program,
+        ],
+        ctx.signer_seeds,
+    )
+    .map_err(Into::into)
+}
+
+#[derive(Accounts)]
+pub struct DelegateV1<'info> {
+    /// CHECK: metadata delegate record PDA for metadata roles (writable), or the metadata program
+    pub delegate_record: AccountInfo<'info>,
+    /// CHECK: account to approve as delegate
+    pub delegate: AccountInfo<'info>,
+    /// CHECK: metadata PDA of the mint, validated by the metadata program
+    #[account(mut)]
+    pub metadata: AccountInfo<'info>,
+    /// CHECK: master edition PDA of the mint, or the metadata program
+    pub master_edition: AccountInfo<'info>,
+    /// CHECK: token record PDA of `token` for programmable token roles (writable), or the
+    /// metadata program
+    pub token_record: AccountInfo<'info>,
+    /// CHECK: mint of the asset, validated by the metadata program
+    pub mint: AccountInfo<'info>,
+    /// CHECK: token account to delegate for token roles (writable), or the metadata program
+    pub token: AccountInfo<'info>,
+    /// CHECK: update authority for metadata roles, owner of `token` for token roles
+    #[account(signer)]
+    pub authority: AccountInfo<'info>,
+    /// CHECK: pays for the records created
+    #[account(mut, signer)]
+    pub payer: AccountInfo<'info>,
+    pub system_program: Program<'info, System>,
+    /// CHECK: instructions sysvar
+    #[account(address = sysvar::instructions::ID)]
+    pub sysvar_instructions: AccountInfo<'info>,
+    /// CHECK: token program for token roles, or the metadata program
+    pub spl_token_program: AccountInfo<'info>,
+    /// CHECK: authorization rules program, or the metadata program
+    pub authorization_rules_program: AccountInfo<'info>,
+    /// CHECK: rule set of the asset, or the metadata program
+    pub authorization_rules: AccountInfo<'info>,
+}
+
+/// Revokes `delegate` from `role`.
+pub fn revoke_v1<'a, 'b, 'c, 'info>(
+    ctx: CpiContext<'a, 'b, 'c, 'info, RevokeV1<'info>>,
+    role: DelegateRole,
+) -> Result<()> {
+    let accounts = ctx.accounts;
+    let ix = Instruction {
+        program_id: mpl_token_metadata::ID,
+        accounts: vec![
+            optional_meta(&accounts.delegate_record, true),
+            AccountMeta::new_readonly(accounts.delegate.key(), false),
+            AccountMeta::new(accounts.metadata.key(), false),
+            optional_meta(&accounts.master_edition, false),
+            optional_meta(&accounts.token_record, true),
+            AccountMeta::new_readonly(accounts.mint.key(), false),
+            optional_meta(&accounts.token, true),
+            AccountMeta::new_readonly(accounts.authority.key(), true),
+            AccountMeta::new(accounts.payer.key(), true),
+            AccountMeta::new_readonly(accounts.system_program.key(), false),
+            AccountMeta::new_readonly(accounts.sysvar_instructions.key(), false),
+            optional_meta(&accounts.spl_token_program, false),
+            optional_meta(&accounts.authorization_rules_program, false),
+            optional_meta(&accounts.authorization_rules, false),
+        ],
+        data: instruction_data(REVOKE, role.revoke_args()),
+    };
+    solana_program::program::invoke_signed(
+        &ix,
+        &[
+            accounts.delegate_record,
+            accounts.delegate,
+            accounts.metadata,
+            accounts.master_edition,
+            accounts.token_record,
+            accounts.mint,
+            accounts.token,
+            accounts.authority,
+            accounts.payer,
+            accounts.system_program.to_account_info(),
+            accounts.sysvar_instructions,
+            accounts.spl_token_program,
+            accounts.authorization_rules_program,
+            accounts.authorization_rules,
+            ctx.program,
+        ],
+        ctx.signer_seeds,
+    )
+    .map_err(Into::into)
+}
+
+#[derive(Accounts)]
+pub struct RevokeV1<'info> {
+    /// CHECK: metadata delegate record PDA for metadata roles (writable), or the metadata program
+    pub delegate_record: AccountInfo<'info>,
+    /// CHECK: delegate to revoke
+    pub delegate: AccountInfo<'info>,
+    /// CHECK: metadata PDA of the mint, validated by the metadata program
+    #[account(mut)]
+    pub metadata: AccountInfo<'info>,
+    /// CHECK: master edition PDA of the mint, or the metadata program
+    pub master_edition: AccountInfo<'info>,
+    /// CHECK: token record PDA of `token` for programmable token roles (writable), or the
+    /// metadata program
+    pub token_record: AccountInfo<'info>,
+    /// CHECK: mint of the asset, validated by the metadata program
+    pub mint: AccountInfo<'info>,
+    /// CHECK: delegated token account for token roles (writable), or the metadata program
+    pub token: AccountInfo<'info>,
+    /// CHECK: update authority for metadata roles, owner of `token` for token roles
+    #[account(signer)]
+    pub authority: AccountInfo<'info>,
+    /// CHECK: payer of the instruction
+    #[account(mut, signer)]
+    pub payer: AccountInfo<'info>,
+    pub system_program: Program<'info, System>,
+    /// CHECK: instructions sysvar
+    #[account(address = sysvar::instructions::ID)]
+    pub sysvar_instructions: AccountInfo<'info>,
+    /// CHECK: token program for token roles, or the metadata program
+    pub spl_token_program: AccountInfo<'info>,
+    /// CHECK: authorization rules program, or the metadata program
+    pub authorization_rules_program: AccountInfo<'info>,
+    /// CHECK: rule set of the asset, or the metadata program
+    pub authorization_rules: AccountInfo<'info>,
+}
+
+/// [`transfer_v1`] for any token standard, passing only the optional accounts the asset uses.
+///
+/// `metadata` must be the metadata PDA of `mint`. For programmable NFTs the token records must
+/// be the PDAs derived for `token` and `destination` and, if the metadata's
+/// `programmable_config` names a rule set, `authorization_rules` must be that rule set.
+/// Otherwise the metadata program is passed in their place, whatever accounts were given.
+pub fn transfer_asset<'a, 'b, 'c, 'info>(
+    mut ctx: CpiContext<'a, 'b, 'c, 'info, TransferV1<'info>>,
+    amount: u64,
+) -> Result<()> {
+    assert_metadata_for_mint(&ctx.accounts.metadata, &ctx.accounts.mint.key())?;
+    let (programmable, rule_set) = {
+        let data = ctx.accounts.metadata.try_borrow_data()?;
+        let reader = MetadataReader::new(&data)?;
+        let programmable = is_programmable_standard(reader.token_standard()?);
+        let rule_set = match reader.programmable_config()? {
+            Some(ProgrammableConfig::V1 { rule_set }) => rule_set,
+            None => None,
+        };
+        (programmable, rule_set)
+    };
+    let program = ctx.program.clone();
+    let accounts = &mut ctx.accounts;
+
+    if programmable {
+        let mint = accounts.mint.key();
+        let (owner_token_record, _) = find_token_record_account(&mint, &accounts.token.key());
+        let (destination_token_record, _) =
+            find_token_record_account(&mint, &accounts.destination.key());
+        if accounts.owner_token_record.key() != owner_token_record
+            || accounts.destination_token_record.key() != destination_token_record
+        {
+            return err!(MplCpiError::InvalidTokenRecord);
+        }
+    } else {
+        accounts.owner_token_record = program.clone();
+        accounts.destination_token_record = program.clone();
+    }
+
+    match rule_set {
+        Some(rule_set) if programmable => {
+            if accounts.authorization_rules.key() != rule_set {
+                return err!(MplCpiError::InvalidRuleSet);
+            }
+        }
+        _ => {
+            accounts.authorization_rules_program = program.clone();
+            accounts.authorization_rules = program;
+        }
+    }
+
+    transfer_v1(ctx, amount)
+}
+
+/// Locks `token` in place, e.g. while staked, as its delegate; the lock counterpart of
+/// `freeze_delegated_account` for programmable NFTs.
+pub fn lock_v1<'a, 'b, 'c, 'info>(ctx: CpiContext<'a, 'b, 'c, 'info, LockV1<'info>>) -> Result<()> {
+    let accounts = ctx.accounts;
+    let ix = Instruction {
+        program_id: mpl_token_metadata::ID,
+        accounts: vec![
+            AccountMeta::new_readonly(accounts.authority.key(), true),
+            optional_meta(&accounts.token_owner, false),
+            AccountMeta::new(accounts.token.key(), false),
+            AccountMeta::new_readonly(accounts.mint.key(), false),
+            AccountMeta::new(accounts.metadata.key(), false),
+            optional_meta(&accounts.edition, false),
+            optional_meta(&accounts.token_record, true),
+            AccountMeta::new(accounts.payer.key(), true),
+            AccountMeta::new_readonly(accounts.system_program.key(), false),
+            AccountMeta::new_readonly(accounts.sysvar_instructions.key(), false),
+            optional_meta(&accounts.spl_token_program, false),
+            optional_meta(&accounts.authorization_rules_program, false),
+            optional_meta(&accounts.authorization_rules, false),
+        ],
+        data: instruction_data(
+            LOCK,
+            LockArgs::V1 {
+                authorization_data: None,
+            },
+        ),
+    };
+    solana_program::program::invoke_signed(
+        &ix,
+        &[
+            accounts.authority,
+            accounts.token_owner,
+            accounts.token,
+            accounts.mint,
+            accounts.metadata,
+            accounts.edition,
+            accounts.token_record,
+            accounts.payer,
+            accounts.system_program.to_account_info(),
+            accounts.sysvar_instructions,
+            accounts.spl_token_program,
+            accounts.authorization_rules_program,
+            accounts.authorization_rules,
+            ctx.program,
+        ],
+        ctx.signer_seeds,
+    )
+    .map_err(Into::into)
+}
+
+#[derive(Accounts)]
+pub struct LockV1<'info> {
+    /// CHECK: utility or staking delegate of `token`
+    #[account(signer)]
+    pub authority: AccountInfo<'info>,
+    /// CHECK: owner of `token`, or the metadata program
+    pub token_owner: AccountInfo<'info>,
+    /// CHECK: token account holding the asset
+    #[account(mut)]
+    pub token: AccountInfo<'info>,
+    /// CHECK: mint of the asset, validated by the metadata program
+    pub mint: AccountInfo<'info>,
+    /// CHECK: metadata PDA of the mint, validated by the metadata program
+    #[account(mut)]
+    pub metadata: AccountInfo<'info>,
+    /// CHECK: edition PDA of the mint, or the metadata program
+    pub edition: AccountInfo<'info>,
+    /// CHECK: token record PDA of `token` for programmable NFTs (writable), or the metadata program
+    pub token_record: AccountInfo<'info>,
+    /// CHECK: payer of the instruction
+    #[account(mut, signer)]
+    pub payer: AccountInfo<'info>,
+    pub system_program: Program<'info, System>,
+    /// CHECK: instructions sysvar
+    #[account(address = sysvar::instructions::ID)]
+    pub sysvar_instructions: AccountInfo<'info>,
+    /// CHECK: token program, or the metadata program
+    pub spl_token_program: AccountInfo<'info>,
+    /// CHECK: authorization rules program, or the metadata program
+    pub authorization_rules_program: AccountInfo<'info>,
+    /// CHECK: rule set of the asset, or the metadata program
+    pub authorization_rules: AccountInfo<'info>,
+}
+
+/// Unlocks a token account locked with [`lock_v1`].
+pub fn unlock_v1<'a, 'b, 'c, 'info>(
+    ctx: CpiContext<'a, 'b, 'c, 'info, UnlockV1<'info>>,
+) -> Result<()> {
+    let accounts = ctx.accounts;
+    let ix = Instruction {
+        program_id: mpl_token_metadata::ID,
+        accounts: vec![
+            AccountMeta::new_readonly(accounts.authority.key(), true),
+            optional_meta(&accounts.token_owner, false),
+            AccountMeta::new(accounts.token.key(), false),
+            AccountMeta::new_readonly(accounts.mint.key(), false),
+            AccountMeta::new(accounts.metadata.key(), false),
+            optional_meta(&accounts.edition, false),
+            optional_meta(&accounts.token_record, true),
+            AccountMeta::new(accounts.payer.key(), true),
+            AccountMeta::new_readonly(accounts.system_program.key(), false),
+            AccountMeta::new_readonly(accounts.sysvar_instructions.key(), false),
+            optional_meta(&accounts.spl_token_program, false),
+            optional_meta(&accounts.authorization_rules_program, false),
+            optional_meta(&accounts.authorization_rules, false),
+        ],
+        data: instruction_data(
+            UNLOCK,
+            UnlockArgs::V1 {
+                authorization_data: None,
+            },
+        ),
+    };
+    solana_program::program::invoke_signed(
+        &ix,
+        &[
+            accounts.authority,
+            accounts.token_owner,
+            accounts.token,
+            accounts.mint,
+            accounts.metadata,
+            accounts.edition,
+            accounts.token_record,
+            accounts.payer,
+            accounts.system_program.to_account_info(),
+            accounts.sysvar_instructions,
+            accounts.spl_token_program,
+            accounts.authorization_rules_program,
+            accounts.authorization_rules,
+            ctx.program,
+        ],
+        ctx.signer_seeds,
+    )
+    .map_err(Into::into)
+}
+
+#[derive(Accounts)]
+pub struct UnlockV1<'info> {
+    /// CHECK: utility or staking delegate of `token`
+    #[account(signer)]
+    pub authority: AccountInfo<'info>,
+    /// CHECK: owner of `token`, or the metadata program
+    pub token_owner: AccountInfo<'info>,
+    /// CHECK: token account holding the asset
+    #[account(mut)]
+    pub token: AccountInfo<'info>,
+    /// CHECK: mint of the asset, validated by the metadata program
+    pub mint: AccountInfo<'info>,
+    /// CHECK: metadata PDA of the mint, validated by the metadata program
+    #[account(mut)]
+    pub metadata: AccountInfo<'info>,
+    /// CHECK: edition PDA of the mint, or the metadata program
+    pub edition: AccountInfo<'info>,
+    /// CHECK: token record PDA of `token` for programmable NFTs (writable), or the metadata program
+    pub token_record: AccountInfo<'info>,
+    /// CHECK: payer of the instruction
+    #[account(mut, signer)]
+    pub payer: AccountInfo<'info>,
+    pub system_program: Program<'info, System>,
+    /// CHECK: instructions sysvar
+    #[account(address = sysvar::instructions::ID)]
+    pub sysvar_instructions: AccountInfo<'info>,
+    /// CHECK: token program, or the metadata program
+    pub spl_token_program: AccountInfo<'info>,
+    /// CHECK: authorization rules program, or the metadata program
+    pub authorization_rules_program: AccountInfo<'info>,
+    /// CHECK: rule set of the asset, or the metadata program
+    pub authorization_rules: AccountInfo<'info>,
+}
+
+/// Locks a staked asset with [`lock_v1`] if it is a programmable NFT, and otherwise freezes it
+/// with `freeze_delegated_account`, which also needs `authority` writable and the token
+/// program as `spl_token_program`.
+pub fn lock_asset<'a, 'b, 'c, 'info>(
+    ctx: CpiContext<'a, 'b, 'c, 'info, LockV1<'info>>,
+) -> Result<()> {
+    if is_programmable(&ctx.accounts.metadata, &ctx.accounts.mint.key())? {
+        return lock_v1(ctx);
+    }
+    let accounts = ctx.accounts;
+    crate::freeze_delegated_account(CpiContext::new_with_signer(
+        ctx.program,
+        FreezeDelegatedAccount {
+            delegate: accounts.authority.clone(),
+            token_account: accounts.token,
+            edition: accounts.edition,
+            update_authority: accounts.authority,
+            mint: accounts.mint,
+            token_program: Program::try_from(&accounts.spl_token_program)?,
+        },
+        ctx.signer_seeds,
+    ))
+}
+
+/// Unlocks an asset locked with [`lock_asset`], with [`unlock_v1`] or `thaw_delegated_account`
+/// depending on its token standard.
+pub fn unlock_asset<'a, 'b, 'c, 'info>(
+    ctx: CpiContext<'a, 'b, 'c, 'info, UnlockV1<'info>>,
+) -> Result<()> {
+    if is_programmable(&ctx.accounts.metadata, &ctx.accounts.mint.key())? {
+        return unlock_v1(ctx);
+    }
+    let accounts = ctx.accounts;
+    crate::thaw_delegated_account(CpiContext::new_with_signer(
+        ctx.program,
+        ThawDelegatedAccount {
+            delegate: accounts.authority.clone(),
+            token_account: accounts.token,
+            edition: accounts.edition,
+            update_authority: accounts.authority,
+            mint: accounts.mint,
+            token_program: Program::try_from(&accounts.spl_token_program)?,
+        },
+        ctx.signer_seeds,
+    ))
+}
+
+fn is_programmable(metadata: &AccountInfo, mint: &Pubkey) -> Result<bool> {
+    assert_metadata_for_mint(metadata, mint)?;
+    let data = metadata.try_borrow_data()?;
+    Ok(is_programmable_standard(
+        MetadataReader::new(&data)?.token_standard()?,
+    ))
+}
+
+fn is_programmable_standard(token_standard: Option<TokenStandard>) -> bool {
+    matches!(
+        token_standard,
+        Some(
+            TokenStandard::ProgrammableNonFungible | TokenStandard::ProgrammableNonFungibleEdition
+        )
+    )
+}
+
+/// Meta of an optional account, read-only when the metadata program stands in for it.
+fn optional_meta(account: &AccountInfo, is_writable: bool) -> AccountMeta {
+    if account.key() == mpl_token_metadata::ID || !is_writable {
+        AccountMeta::new_readonly(account.key(), false)
+    } else {
+        AccountMeta::new(account.key(), false)
+    }
+}
+
+fn instruction_data(discriminator: u8, args: impl AnchorSerialize) -> Vec<u8> {
+    let mut data = vec![discriminator];
+    // Serializing into a `Vec` cannot fail.
+    args.serialize(&mut data).unwrap();
+    data
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const AMOUNT: u64 = 0x0102_0304_0506_0708;
+
+    fn amount() -> [u8; 8] {
+        [8, 7, 6, 5, 4, 3, 2, 1]
+    }
+
+    #[test]
+    fn create_args_layout() {
+        let rule_set = Pubkey::new_unique();
+        let args = CreateArgs::V1 {
+            asset_data: AssetData {
+                name: "N".to_string(),
+                symbol: "S".to_string(),
+                uri: "U".to_string(),
+                seller_fee_basis_points: 0x0201,
+                creators: None,
+                primary_sale_happened: false,
+                is_mutable: true,
+                token_standard: TokenStandard::ProgrammableNonFungible,
+                collection: None,
+                uses: None,
+                collection_details: None,
+                rule_set: Some(rule_set),
+            },
+            decimals: Some(0),
+            print_supply: Some(PrintSupply::Limited(AMOUNT)),
+        };
+        let expected = [
+            &[CREATE, 0][..],
+            &[1, 0, 0, 0, b'N'],
+            &[1, 0, 0, 0, b'S'],
+            &[1, 0, 0, 0, b'U'],
+            &[1, 2],
+            // creators, primary_sale_happened, is_mutable, token_standard, collection, uses,
+            // collection_details
+            &[0, 0, 1, 4, 0, 0, 0],
+            &[1],
+            rule_set.as_ref(),
+            // decimals
+            &[1, 0],
+            // print_supply
+            &[1, 1],
+            &amount(),
+        ]
+        .concat();
+        assert_eq!(instruction_data(CREATE, args), expected);
+    }
+
+    #[test]
+    fn print_supply_layout() {
+        assert_eq!(PrintSupply::Zero.try_to_vec().unwrap(), [0]);
+        assert_eq!(PrintSupply::Unlimited.try_to_vec().unwrap(), [2]);
+    }
+
+    #[test]
+    fn mint_args_layout() {
+        let args = MintArgs::V1 {
+            amount: AMOUNT,
+            authorization_data: None,
+        };
+        let expected = [&[MINT, 0][..], &amount(), &[0]].concat();
+        assert_eq!(instruction_data(MINT, args), expected);
+    }
+
+    #[test]
+    fn transfer_args_layout() {
+        let args = TransferArgs::V1 {
+            amount: AMOUNT,
+            authorization_data: None,
+        };
+        let expected = [&[TRANSFER, 0][..], &amount(), &[0]].concat();
+        assert_eq!(instruction_data(TRANSFER, args), expected);
+    }
+
+    #[test]
+    fn lock_and_unlock_args_layout() {
+        let lock = LockArgs::V1 {
+            authorization_data: None,
+        };
+        let unlock = UnlockArgs::V1 {
+            authorization_data: None,
+        };
+        assert_eq!(instruction_data(LOCK, lock), [LOCK, 0, 0]);
+        assert_eq!(instruction_data(UNLOCK, unlock), [UNLOCK, 0, 0]);
+    }
+
+    #[test]
+    fn delegate_args_layout() {
+        let locked_address = Pubkey::new_unique();
+        let cases = [
+            (DelegateRole::Collection, vec![0, 0]),
+            (DelegateRole::Sale, [&[1][..], &amount(), &[0]].concat()),
+            (DelegateRole::Transfer, [&[2][..], &amount(), &[0]].concat()),
+            (DelegateRole::Data, vec![3, 0]),
+            (DelegateRole::Utility, [&[4][..], &amount(), &[0]].concat()),
+            (DelegateRole::Staking, [&[5][..], &amount(), &[0]].concat()),
+            (DelegateRole::Standard, [&[6][..], &amount()].concat()),
+            (
+                DelegateRole::LockedTransfer { locked_address },
+                [&[7][..], &amount(), locked_address.as_ref(), &[0]].concat(),
+            ),
+            (DelegateRole::ProgrammableConfig, vec![8, 0]),
+        ];
+        for (role, args) in cases {
+            let expected = [&[DELEGATE][..], &args].concat();
+            assert_eq!(
+                instruction_data(DELEGATE, role.delegate_args(AMOUNT)),
+                expected,
+                "{:?}",
+                role
+            );
+        }
+    }
+
+    #[test]
+    fn revoke_args_layout() {
+        let cases = [
+            (DelegateRole::Collection, 0),
+            (DelegateRole::Sale, 1),
+            (DelegateRole::Transfer, 2),
+            (DelegateRole::Data, 3),
+            (DelegateRole::Utility, 4),
+            (DelegateRole::Staking, 5),
+            (DelegateRole::Standard, 6),
+            (
+                DelegateRole::LockedTransfer {
+                    locked_address: Pubkey::new_unique(),
+                },
+                7,
+            ),
+            (DelegateRole::ProgrammableConfig, 8),
+        ];
+        for (role, variant) in cases {
+            assert_eq!(
+                instruction_data(REVOKE, role.revoke_args()),
+                [REVOKE, variant],
+                "{:?}",
+                role
+            );
+        }
+    }
+
+    #[test]
+    fn token_record_address() {
+        let mint = Pubkey::new_unique();
+        let token = Pubkey::new_unique();
+        let expected = Pubkey::find_program_address(
+            &[
+                b"metadata",
+                mpl_token_metadata::ID.as_ref(),
+                mint.as_ref(),
+                b"token_record",
+                token.as_ref(),
+            ],
+            &mpl_token_metadata::ID,
+        );
+        assert_eq!(find_token_record_account(&mint, &token), expected);
+    }
+
+    #[test]
+    fn metadata_delegate_record_address() {
+        let mint = Pubkey::new_unique();
+        let update_authority = Pubkey::new_unique();
+        let delegate = Pubkey::new_unique();
+        let token = Pubkey::new_unique();
+        let cases: [(DelegateRole, &[u8]); 3] = [
+            (DelegateRole::Collection, b"collection_delegate"),
+            (DelegateRole::Data, b"data_delegate"),
+            (
+                DelegateRole::ProgrammableConfig,
+                b"programmable_config_delegate",
+            ),
+        ];
+        for (role, seed) in cases {
+            let expected = Pubkey::find_program_address(
+                &[
+                    b"metadata",
+                    mpl_token_metadata::ID.as_ref(),
+                    mint.as_ref(),
+                    seed,
+                    update_authority.as_ref(),
+                    delegate.as_ref(),
+                ],
+                &mpl_token_metadata::ID,
+            );
+            assert_eq!(
+                role.find_record_account(&mint, &update_authority, &delegate, &token),
+                Some(expected)
+            );
+        }
+    }
+
+    #[test]
+    fn token_delegate_record_address() {
+        let mint = Pubkey::new_unique();
+        let update_authority = Pubkey::new_unique();
+        let delegate = Pubkey::new_unique();
+        let token = Pubkey::new_unique();
+        assert_eq!(
+            DelegateRole::Utility.find_record_account(&mint, &update_authority, &delegate, &token),
+            Some(find_token_record_account(&mint, &token))
+        );
+        assert_eq!(
+            DelegateRole::Standard.find_record_account(&mint, &update_authority, &delegate, &token),
+            None
+        );
+    }
+}
@@ -0,0 +1,440 @@
+use anchor_lang::prelude::*;
+use mpl_token_metadata::state::{
+    Collection, CollectionDetails, Creator, Key as MetadataKey, Uses, MAX_CREATOR_LEN,
+};
+
+use crate::types::{ProgrammableConfig, TokenStandard};
+
+const UPDATE_AUTHORITY_OFFSET: usize = 1;
+const MINT_OFFSET: usize = 33;
+const NAME_OFFSET: usize = 65;
+
+/// Reads individual fields of a metadata account by walking its Borsh layout in place,
+/// without deserializing the whole `Metadata` struct.
+///
+/// Like `Metadata::deserialize`, a corrupted `token_standard`/`collection`/`uses` tail is read
+/// as all three being `None`. Token standards are read as [`TokenStandard`], so programmable NFTs
+/// are not mistaken for a corrupted tail.
+pub struct MetadataReader<'a> {
+    data: &'a [u8],
+}
+
+impl<'a> MetadataReader<'a> {
+    /// Wraps the data of a metadata account, e.g. `&metadata.try_borrow_data()?`.
+    pub fn new(data: &'a [u8]) -> Result<Self> {
+        if data.first() != Some(&(MetadataKey::MetadataV1 as u8)) {
+            return err!(ErrorCode::AccountDiscriminatorMismatch);
+        }
+        Ok(Self { data })
+    }
+
+    pub fn key(&self) -> MetadataKey {
+        MetadataKey::MetadataV1
+    }
+
+    pub fn update_authority(&self) -> Result<Pubkey> {
+        self.pubkey_at(UPDATE_AUTHORITY_OFFSET)
+    }
+
+    pub fn mint(&self) -> Result<Pubkey> {
+        self.pubkey_at(MINT_OFFSET)
+    }
+
+    pub fn name(&self) -> Result<&'a str> {
+        self.string_at(NAME_OFFSET).map(|(name, _)| name)
+    }
+
+    pub fn symbol(&self) -> Result<&'a str> {
+        let (_, offset) = self.string_at(NAME_OFFSET)?;
+        self.string_at(offset).map(|(symbol, _)| symbol)
+    }
+
+    pub fn uri(&self) -> Result<&'a str> {
+        let (_, offset) = self.string_at(NAME_OFFSET)?;
+        let (_, offset) = self.string_at(offset)?;
+        self.string_at(offset).map(|(uri, _)| uri)
+    }
+
+    pub fn seller_fee_basis_points(&self) -> Result<u16> {
+        let offset = self.seller_fee_basis_points_offset()?;
+        Ok(u16::from_le_bytes(
+            self.bytes(offset, 2)?.try_into().unwrap(),
+        ))
+    }
+
+    /// The creators array; the only accessor that allocates.
+    pub fn creators(&self) -> Result<Vec<Creator>> {
+        let offset = self.seller_fee_basis_points_offset()? + 2;
+        match self.option_at(offset)? {
+            Some(offset) => {
+                let len = self.u32_at(offset)? as usize;
+                let mut bytes = self.bytes(offset + 4, len * MAX_CREATOR_LEN)?;
+                (0..len)
+                    .map(|_| Creator::deserialize(&mut bytes).map_err(Into::into))
+                    .collect()
+            }
+            None => Ok(Vec::new()),
+        }
+    }
+
+    pub fn primary_sale_happened(&self) -> Result<bool> {
+        let offset = self.primary_sale_happened_offset()?;
+        Ok(self.bytes(offset, 1)?[0] != 0)
+    }
+
+    pub fn is_mutable(&self) -> Result<bool> {
+        let offset = self.primary_sale_happened_offset()? + 1;
+        Ok(self.bytes(offset, 1)?[0] != 0)
+    }
+
+    pub fn edition_nonce(&self) -> Result<Option<u8>> {
+        let offset = self.primary_sale_happened_offset()? + 2;
+        match self.option_at(offset)? {
+            Some(offset) => Ok(Some(self.bytes(offset, 1)?[0])),
+            None => Ok(None),
+        }
+    }
+
+    pub fn token_standard(&self) -> Result<Option<TokenStandard>> {
+        Ok(self.tail()?.token_standard)
+    }
+
+    pub fn collection(&self) -> Result<Option<Collection>> {
+        Ok(self.tail()?.collection)
+    }
+
+    pub fn uses(&self) -> Result<Option<Uses>> {
+        Ok(self.tail()?.uses)
+    }
+
+    pub fn collection_details(&self) -> Result<Option<CollectionDetails>> {
+        let offset = match self.tail()?.end {
+            Some(offset) => offset,
+            None => return Ok(None),
+        };
+        let mut rest = self.data.get(offset..).unwrap_or_default();
+        Ok(Option::<CollectionDetails>::deserialize(&mut rest).unwrap_or_default())
+    }
+
+    /// The rule set configuration of programmable NFTs, stored after `collection_details`.
+    pub fn programmable_config(&self) -> Result<Option<ProgrammableConfig>> {
+        let offset = match self.tail()?.end {
+            Some(offset) => offset,
+            None => return Ok(None),
+        };
+        let mut rest = self.data.get(offset..).unwrap_or_default();
+        match Option::<CollectionDetails>::deserialize(&mut rest) {
+            Ok(_) => Ok(Option::<ProgrammableConfig>::deserialize(&mut rest).unwrap_or_default()),
+            Err(_) => Ok(None),
+        }
+    }
+
+    fn seller_fee_basis_points_offset(&self) -> Result<usize> {
+        let (_, offset) = self.string_at(NAME_OFFSET)?;
+        let (_, offset) = self.string_at(offset)?;
+        let (_, offset) = self.string_at(offset)?;
+        Ok(offset)
+    }
+
+    fn primary_sale_happened_offset(&self) -> Result<usize> {
+        let offset = self.seller_fee_basis_points_offset()? + 2;
+        match self.option_at(offset)? {
+            Some(offset) => Ok(offset + 4 + self.u32_at(offset)? as usize * MAX_CREATOR_LEN),
+            None => Ok(offset + 1),
+        }
+    }
+
+    fn tail(&self) -> Result<Tail> {
+        let nonce_offset = self.primary_sale_happened_offset()? + 2;
+        let offset = match self.option_at(nonce_offset)? {
+            Some(value_offset) => value_offset + 1,
+            None => nonce_offset + 1,
+        };
+        let mut rest = self.data.get(offset..).unwrap_or_default();
+        let start = rest.len();
+        let token_standard = Option::<TokenStandard>::deserialize(&mut rest);
+        let collection = Option::<Collection>::deserialize(&mut rest);
+        let uses = Option::<Uses>::deserialize(&mut rest);
+        Ok(match (token_standard, collection, uses) {
+            (Ok(token_standard), Ok(collection), Ok(uses)) => Tail {
+                token_standard,
+                collection,
+                uses,
+                end: Some(offset + start - rest.len()),
+            },
+            _ => Tail {
+                token_standard: None,
+                collection: None,
+                uses: None,
+                end: None,
+            },
+        })
+    }
+
+    /// Offset of the value when the `Option` tag at `offset` is `Some`.
+    fn option_at(&self, offset: usize) -> Result<Option<usize>> {
+        match self.bytes(offset, 1)?[0] {
+            0 => Ok(None),
+            _ => Ok(Some(offset + 1)),
+        }
+    }
+
+    /// The string at `offset`, with padding stripped, and the offset of the next field.
+    fn string_at(&self, offset: usize) -> Result<(&'a str, usize)> {
+        let len = self.u32_at(offset)? as usize;
+        let bytes = self.bytes(offset + 4, len)?;
+        let value = std::str::from_utf8(bytes)
+            .map_err(|_| ErrorCode::AccountDidNotDeserialize)?
+            .trim_end_matches(char::from(0));
+        Ok((value, offset + 4 + len))
+    }
+
+    fn u32_at(&self, offset: usize) -> Result<u32> {
+        Ok(u32::from_le_bytes(
+            self.bytes(offset, 4)?.try_into().unwrap(),
+        ))
+    }
+
+    fn pubkey_at(&self, offset: usize) -> Result<Pubkey> {
+        Ok(Pubkey::new(self.bytes(offset, 32)?))
+    }
+
+    fn bytes(&self, offset: usize, len: usize) -> Result<&'a [u8]> {
+        self.data
+            .get(offset..offset + len)
+            .ok_or_else(|| error!(ErrorCode::AccountDidNotDeserialize))
+    }
+}
+
+struct Tail {
+    token_standard: Option<TokenStandard>,
+    collection: Option<Collection>,
+    uses: Option<Uses>,
+    /// Offset just past `uses`, if the tail deserialized cleanly.
+    end: Option<usize>,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use mpl_token_metadata::state::{
+        Data, Metadata, TokenStandard as MetadataTokenStandard, UseMethod, MAX_METADATA_LEN,
+        MAX_NAME_LENGTH, MAX_SYMBOL_LENGTH, MAX_URI_LENGTH,
+    };
+
+    fn metadata() -> Metadata {
+        Metadata {
+            key: MetadataKey::MetadataV1,
+            update_authority: Pubkey::new_unique(),
+            mint: Pubkey::new_unique(),
+            data: Data {
+                name: "Name".to_string(),
+                symbol: "SYM".to_string(),
+                uri: "https://example.com/0.json".to_string(),
+                seller_fee_basis_points: 500,
+                creators: Some(vec![
+                    Creator {
+                        address: Pubkey::new_unique(),
+                        verified: true,
+                        share: 60,
+                    },
+                    Creator {
+                        address: Pubkey::new_unique(),
+                        verified: false,
+                        share: 40,
+                    },
+                ]),
+            },
+            primary_sale_happened: true,
+            is_mutable: true,
+            edition_nonce: Some(254),
+            token_standard: Some(MetadataTokenStandard::NonFungible),
+            collection: Some(Collection {
+                verified: true,
+                key: Pubkey::new_unique(),
+            }),
+            uses: Some(Uses {
+                use_method: UseMethod::Multiple,
+                remaining: 3,
+                total: 5,
+            }),
+            collection_details: Some(CollectionDetails::V1 { size: 7 }),
+        }
+    }
+
+    /// Serializes `metadata` into a zero-padded buffer of the size of a metadata account.
+    fn account_data(metadata: &Metadata) -> Vec<u8> {
+        let mut data = metadata.try_to_vec().unwrap();
+        data.resize(MAX_METADATA_LEN, 0);
+        data
+    }
+
+    fn pad(value: &str, len: usize) -> String {
+        format!("{:\0<len$}", value, len = len)
+    }
+
+    fn assert_reads(reader: &MetadataReader, metadata: &Metadata) {
+        assert_eq!(
+            reader.update_authority().unwrap(),
+            metadata.update_authority
+        );
+        assert_eq!(reader.mint().unwrap(), metadata.mint);
+        assert_eq!(
+            reader.name().unwrap(),
+            metadata.data.name.trim_end_matches('\0')
+        );
+        assert_eq!(
+            reader.symbol().unwrap(),
+            metadata.data.symbol.trim_end_matches('\0')
+        );
+        assert_eq!(
+            reader.uri().unwrap(),
+            metadata.data.uri.trim_end_matches('\0')
+        );
+        assert_eq!(
+            reader.seller_fee_basis_points().unwrap(),
+            metadata.data.seller_fee_basis_points
+        );
+        assert_eq!(
+            reader.creators().unwrap(),
+            metadata.data.creators.clone().unwrap_or_default()
+        );
+        assert_eq!(
+            reader.primary_sale_happened().unwrap(),
+            metadata.primary_sale_happened
+        );
+        assert_eq!(reader.is_mutable().unwrap(), metadata.is_mutable);
+        assert_eq!(reader.edition_nonce().unwrap(), metadata.edition_nonce);
+        assert_eq!(
+            reader.token_standard().unwrap(),
+            metadata.token_standard.clone().map(Into::into)
+        );
+        assert_eq!(reader.collection().unwrap(), metadata.collection);
+        assert_eq!(reader.uses().unwrap(), metadata.uses);
+        assert_eq!(
+            reader.collection_details().unwrap(),
+            metadata.collection_details
+        );
+        assert_eq!(reader.programmable_config().unwrap(), None);
+    }
+
+    #[test]
+    fn reads_fully_populated_metadata() {
+        let metadata = metadata();
+        let data = account_data(&metadata);
+        assert_reads(&MetadataReader::new(&data).unwrap(), &metadata);
+    }
+
+    #[test]
+    fn reads_metadata_without_optional_fields() {
+        let mut metadata = metadata();
+        metadata.data.creators = None;
+        metadata.edition_nonce = None;
+        metadata.token_standard = None;
+        metadata.collection = None;
+        metadata.uses = None;
+        metadata.collection_details = None;
+        let data = account_data(&metadata);
+        assert_reads(&MetadataReader::new(&data).unwrap(), &metadata);
+    }
+
+    #[test]
+    fn reads_metadata_without_creators_or_edition_nonce() {
+        let mut metadata = metadata();
+        metadata.data.creators = None;
+        metadata.edition_nonce = None;
+        let data = account_data(&metadata);
+        assert_reads(&MetadataReader::new(&data).unwrap(), &metadata);
+    }
+
+    #[test]
+    fn strips_padding_from_puffed_strings() {
+        let mut metadata = metadata();
+        metadata.data.name = pad("Name", MAX_NAME_LENGTH);
+        metadata.data.symbol = pad("SYM", MAX_SYMBOL_LENGTH);
+        metadata.data.uri = pad("https://example.com/0.json", MAX_URI_LENGTH);
+        let data = account_data(&metadata);
+        let reader = MetadataReader::new(&data).unwrap();
+        assert_eq!(reader.name().unwrap(), "Name");
+        assert_eq!(reader.symbol().unwrap(), "SYM");
+        assert_eq!(reader.uri().unwrap(), "https://example.com/0.json");
+        assert_reads(&reader, &metadata);
+    }
+
+    #[test]
+    fn reads_corrupted_tail_as_none() {
+        let mut metadata = metadata();
+        metadata.token_standard = None;
+        metadata.collection = None;
+        metadata.uses = None;
+        metadata.collection_details = None;
+        let mut data = account_data(&metadata);
+        // `token_standard` is `Some` with a variant that does not exist.
+        let tail = metadata.try_to_vec().unwrap().len() - 4;
+        data[tail] = 1;
+        data[tail + 1] = 0xff;
+
+        let reader = MetadataReader::new(&data).unwrap();
+        assert_eq!(reader.name().unwrap(), "Name");
+        assert_eq!(reader.edition_nonce().unwrap(), Some(254));
+        assert_eq!(reader.token_standard().unwrap(), None);
+        assert_eq!(reader.collection().unwrap(), None);
+        assert_eq!(reader.uses().unwrap(), None);
+        assert_eq!(reader.collection_details().unwrap(), None);
+        assert_eq!(reader.programmable_config().unwrap(), None);
+    }
+
+    #[test]
+    fn reads_programmable_standard_and_config() {
+        let mut metadata = metadata();
+        let collection = metadata.collection.clone();
+        metadata.token_standard = None;
+        metadata.collection = None;
+        metadata.uses = None;
+        metadata.collection_details = None;
+        let mut data = metadata.try_to_vec().unwrap();
+        data.truncate(data.len() - 4);
+        let rule_set = Pubkey::new_unique();
+        Some(TokenStandard::ProgrammableNonFungibleEdition)
+            .serialize(&mut data)
+            .unwrap();
+        collection.serialize(&mut data).unwrap();
+        None::<Uses>.serialize(&mut data).unwrap();
+        None::<CollectionDetails>.serialize(&mut data).unwrap();
+        Some(ProgrammableConfig::V1 {
+            rule_set: Some(rule_set),
+        })
+        .serialize(&mut data)
+        .unwrap();
+        data.resize(MAX_METADATA_LEN, 0);
+
+        let reader = MetadataReader::new(&data).unwrap();
+        assert_eq!(
+            reader.token_standard().unwrap(),
+            Some(TokenStandard::ProgrammableNonFungibleEdition)
+        );
+        assert_eq!(reader.collection().unwrap(), collection);
+        assert_eq!(reader.uses().unwrap(), None);
+        assert_eq!(reader.collection_details().unwrap(), None);
+        assert_eq!(
+            reader.programmable_config().unwrap(),
+            Some(ProgrammableConfig::V1 {
+                rule_set: Some(rule_set)
+            })
+        );
+    }
+
+    #[test]
+    fn rejects_other_account_keys() {
+        let mut data = account_data(&metadata());
+        data[0] = MetadataKey::MasterEditionV2 as u8;
+        assert!(MetadataReader::new(&data).is_err());
+    }
+
+    #[test]
+    fn rejects_truncated_data() {
+        let data = account_data(&metadata());
+        let reader = MetadataReader::new(&data[..NAME_OFFSET + 2]).unwrap();
+        assert!(reader.name().is_err());
+    }
+}
@@ -0,0 +1,235 @@
+use anchor_lang::prelude::*;
+use anchor_lang::system_program;
+use anchor_spl::token::{self, TokenAccount};
+use mpl_token_metadata::state::Creator;
+
+use crate::assertions::assert_metadata_for_mint;
+use crate::error::MplCpiError;
+use crate::reader::MetadataReader;
+
+const BASIS_POINTS: u128 = 10_000;
+
+/// Royalty owed on a sale of `price`, rounded down.
+pub fn royalty_amount(price: u64, seller_fee_basis_points: u16) -> Result<u64> {
+    if u128::from(seller_fee_basis_points) > BASIS_POINTS {
+        return err!(MplCpiError::InvalidSellerFeeBasisPoints);
+    }
+    let amount = u128::from(price) * u128::from(seller_fee_basis_points) / BASIS_POINTS;
+    u64::try_from(amount).map_err(|_| error!(MplCpiError::NumericalOverflow))
+}
+
+/// Splits the royalty on a sale of `price` between `creators` by share.
+///
+/// Each creator's cut is rounded down and the leftover dust goes to the first creator with a
+/// non-zero share, so the cuts always add up to [`royalty_amount`]. Amounts are returned in the
+/// same order as `creators`.
+pub fn creator_royalties(
+    price: u64,
+    seller_fee_basis_points: u16,
+    creators: &[Creator],
+) -> Result<Vec<(Pubkey, u64)>> {
+    let total = royalty_amount(price, seller_fee_basis_points)?;
+    if creators.is_empty() {
+        return Ok(Vec::new());
+    }
+    let shares: u16 = creators.iter().map(|c| u16::from(c.share)).sum();
+    if shares != 100 {
+        return err!(MplCpiError::InvalidCreatorShares);
+    }
+
+    // A share is at most 100, so each cut is at most `total` and fits back into a u64.
+    let mut payouts: Vec<(Pubkey, u64)> = creators
+        .iter()
+        .map(|c| {
+            let amount = u128::from(total) * u128::from(c.share) / 100;
+            (c.address, amount as u64)
+        })
+        .collect();
+    let paid = payouts
+        .iter()
+        .try_fold(0u64, |paid, (_, amount)| paid.checked_add(*amount))
+        .ok_or_else(|| error!(MplCpiError::NumericalOverflow))?;
+    if let Some(i) = creators.iter().position(|c| c.share > 0) {
+        payouts[i].1 = total
+            .checked_sub(paid)
+            .and_then(|dust| payouts[i].1.checked_add(dust))
+            .ok_or_else(|| error!(MplCpiError::NumericalOverflow))?;
+    }
+    Ok(payouts)
+}
+
+/// [`creator_royalties`] using the seller fee and creators stored on `metadata`, which must be
+/// the metadata PDA of `mint`.
+pub fn metadata_royalties(
+    metadata: &AccountInfo,
+    mint: &Pubkey,
+    price: u64,
+) -> Result<Vec<(Pubkey, u64)>> {
+    assert_metadata_for_mint(metadata, mint)?;
+    let data = metadata.try_borrow_data()?;
+    let reader = MetadataReader::new(&data)?;
+    creator_royalties(
+        price,
+        reader.seller_fee_basis_points()?,
+        &reader.creators()?,
+    )
+}
+
+/// Pays the royalty on a sale of `price` in lamports from `payer` to each creator.
+///
+/// `metadata` must be the metadata PDA of `mint`, the NFT being sold. `ctx.program` is the
+/// system program and `ctx.remaining_accounts` must hold the creator
+/// wallets in the order they appear on the metadata. Returns the total paid.
+pub fn pay_sol_royalties<'a, 'b, 'c, 'info>(
+    ctx: CpiContext<'a, 'b, 'c, 'info, PaySolRoyalties<'info>>,
+    price: u64,
+) -> Result<u64> {
+    let payouts = metadata_royalties(&ctx.accounts.metadata, &ctx.accounts.mint.key(), price)?;
+    if payouts.len() != ctx.remaining_accounts.len() {
+        return err!(MplCpiError::InvalidRoyaltyRecipient);
+    }
+    let mut total = 0;
+    for ((creator, amount), recipient) in payouts.into_iter().zip(ctx.remaining_accounts.iter()) {
+        if recipient.key() != creator {
+            return err!(MplCpiError::InvalidRoyaltyRecipient);
+        }
+        if amount == 0 {
+            continue;
+        }
+        system_program::transfer(
+            CpiContext::new_with_signer(
+                ctx.program.clone(),
+                system_program::Transfer {
+                    from: ctx.accounts.payer.clone(),
+                    to: recipient.clone(),
+                },
+                ctx.signer_seeds,
+            ),
+            amount,
+        )?;
+        total += amount;
+    }
+    Ok(total)
+}
+
+#[derive(Accounts)]
+pub struct PaySolRoyalties<'info> {
+    /// CHECK: metadata the royalty split is read from, checked against `mint`
+    pub metadata: AccountInfo<'info>,
+    /// CHECK: mint of the NFT being sold
+    pub mint: AccountInfo<'info>,
+    /// CHECK: pays the royalties
+    #[account(mut, signer)]
+    pub payer: AccountInfo<'info>,
+}
+
+/// Pays the royalty on a sale of `price` in SPL tokens from `source` to each creator.
+///
+/// `metadata` must be the metadata PDA of `mint`, the NFT being sold. `ctx.program` is the
+/// token program and `ctx.remaining_accounts` must hold a token account
+/// owned by each creator, in the order the creators appear on the metadata and of the same
+/// mint as `source`. Returns the total paid.
+pub fn pay_spl_royalties<'a, 'b, 'c, 'info>(
+    ctx: CpiContext<'a, 'b, 'c, 'info, PaySplRoyalties<'info>>,
+    price: u64,
+) -> Result<u64> {
+    let payouts = metadata_royalties(&ctx.accounts.metadata, &ctx.accounts.mint.key(), price)?;
+    if payouts.len() != ctx.remaining_accounts.len() {
+        return err!(MplCpiError::InvalidRoyaltyRecipient);
+    }
+    let source_mint = token::accessor::mint(&ctx.accounts.source)?;
+    let mut total = 0;
+    for ((creator, amount), recipient) in payouts.into_iter().zip(ctx.remaining_accounts.iter()) {
+        let token_account = Account::<TokenAccount>::try_from(recipient)?;
+        if token_account.owner != creator || token_account.mint != source_mint {
+            return err!(MplCpiError::InvalidRoyaltyRecipient);
+        }
+        if amount == 0 {
+            continue;
+        }
+        token::transfer(
+            CpiContext::new_with_signer(
+                ctx.program.clone(),
+                token::Transfer {
+                    from: ctx.accounts.source.clone(),
+                    to: recipient.clone(),
+                    authority: ctx.accounts.authority.clone(),
+                },
+                ctx.signer_seeds,
+            ),
+            amount,
+        )?;
+        total += amount;
+    }
+    Ok(total)
+}
+
+#[derive(Accounts)]
+pub struct PaySplRoyalties<'info> {
+    /// CHECK: metadata the royalty split is read from, checked against `mint`
+    pub metadata: AccountInfo<'info>,
+    /// CHECK: mint of the NFT being sold
+    pub mint: AccountInfo<'info>,
+    /// CHECK: token account the royalties are paid from
+    #[account(mut)]
+    pub source: AccountInfo<'info>,
+    /// CHECK: owner or delegate of the source token account
+    #[account(signer)]
+    pub authority: AccountInfo<'info>,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn creator(share: u8) -> Creator {
+        Creator {
+            address: Pubkey::new_unique(),
+            verified: true,
+            share,
+        }
+    }
+
+    #[test]
+    fn creator_royalties_handles_max_price() {
+        let creators = [creator(34), creator(33), creator(33)];
+        let payouts = creator_royalties(u64::MAX, 10_000, &creators).unwrap();
+        let paid: u128 = payouts.iter().map(|(_, amount)| u128::from(*amount)).sum();
+        assert_eq!(paid, u128::from(u64::MAX));
+        assert_eq!(
+            payouts[1].1,
+            u64::MAX / 100 * 33 + u64::MAX % 100 * 33 / 100
+        );
+    }
+
+    #[test]
+    fn creator_royalties_gives_dust_to_first_paid_creator() {
+        let creators = [creator(0), creator(50), creator(50)];
+        let payouts = creator_royalties(101, 10_000, &creators).unwrap();
+        let amounts: Vec<u64> = payouts.iter().map(|(_, amount)| *amount).collect();
+        assert_eq!(amounts, [0, 51, 50]);
+    }
+
+    #[test]
+    fn metadata_royalties_rejects_metadata_of_another_mint() {
+        let key = Pubkey::new_unique();
+        let owner = mpl_token_metadata::ID;
+        let mut lamports = 0;
+        let mut data = [0u8; 0];
+        let metadata = AccountInfo::new(
+            &key,
+            false,
+            false,
+            &mut lamports,
+            &mut data,
+            &owner,
+            false,
+            0,
+        );
+        let err = metadata_royalties(&metadata, &Pubkey::new_unique(), 100).unwrap_err();
+        assert_eq!(
+            ProgramError::from(err),
+            ProgramError::from(error!(MplCpiError::InvalidMetadataAccount))
+        );
+    }
+}
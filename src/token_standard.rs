@@ -0,0 +1,41 @@
+use anchor_lang::prelude::*;
+use anchor_lang::solana_program::program_option::COption;
+use anchor_spl::token::Mint;
+use mpl_token_metadata::pda::find_master_edition_account;
+use mpl_token_metadata::state::Key as MetadataKey;
+
+use crate::error::MplCpiError;
+use crate::types::TokenStandard;
+
+/// The token standard the metadata program assigns to `mint`, as `set_token_standard` and
+/// `create_metadata_accounts_v3` would determine it.
+///
+/// With an `edition`, the mint must have zero decimals and a supply of one and is
+/// `NonFungible` for a master edition or `NonFungibleEdition` for a print edition. Without one,
+/// the edition PDA must not be the mint authority and the mint is `FungibleAsset` with zero
+/// decimals or `Fungible` otherwise.
+pub fn infer_token_standard(
+    mint: &Account<'_, Mint>,
+    edition: Option<&AccountInfo>,
+) -> Result<TokenStandard> {
+    let (expected, _) = find_master_edition_account(&mint.key());
+    let edition = match edition {
+        Some(edition) => edition,
+        None if mint.mint_authority == COption::Some(expected) => {
+            return err!(MplCpiError::MintHasEdition)
+        }
+        None if mint.decimals == 0 => return Ok(TokenStandard::FungibleAsset),
+        None => return Ok(TokenStandard::Fungible),
+    };
+    if edition.key() != expected || *edition.owner != mpl_token_metadata::ID {
+        return err!(MplCpiError::InvalidEditionAccount);
+    }
+    if mint.decimals != 0 || mint.supply != 1 {
+        return err!(MplCpiError::UnknownTokenStandard);
+    }
+    match edition.try_borrow_data()?.first() {
+        Some(key) if *key == MetadataKey::MasterEditionV2 as u8 => Ok(TokenStandard::NonFungible),
+        Some(key) if *key == MetadataKey::EditionV1 as u8 => Ok(TokenStandard::NonFungibleEdition),
+        _ => err!(MplCpiError::UnknownTokenStandard),
+    }
+}
@@ -0,0 +1,240 @@
+//! Mirrors of the metadata program's argument types that derive Anchor's serialization traits,
+//! so they can be used as instruction arguments of Anchor programs. The Borsh layout is identical
+//! to the `mpl_token_metadata::state` types they convert into.
+//!
+//! Anchor 0.24's IDL generator only parses the program's own crate, so an instruction argument of
+//! one of these types is referenced by name in the dependent program's IDL without a definition.
+//! The definitions ship in `idl/types.json`, also available as [`IDL_TYPES`], for merging into
+//! the `types` of that IDL as described in the README.
+
+use anchor_lang::prelude::*;
+use mpl_token_metadata::state;
+
+/// Anchor IDL definitions of the types in this module, as a JSON array of `types` entries.
+pub const IDL_TYPES: &str = include_str!("../idl/types.json");
+
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Debug, PartialEq, Eq)]
+pub struct DataV2 {
+    pub name: String,
+    pub symbol: String,
+    pub uri: String,
+    pub seller_fee_basis_points: u16,
+    pub creators: Option<Vec<Creator>>,
+    pub collection: Option<Collection>,
+    pub uses: Option<Uses>,
+}
+
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Debug, PartialEq, Eq)]
+pub struct Creator {
+    pub address: Pubkey,
+    pub verified: bool,
+    pub share: u8,
+}
+
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Debug, PartialEq, Eq)]
+pub struct Collection {
+    pub verified: bool,
+    pub key: Pubkey,
+}
+
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Debug, PartialEq, Eq)]
+pub enum UseMethod {
+    Burn,
+    Multiple,
+    Single,
+}
+
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Debug, PartialEq, Eq)]
+pub struct Uses {
+    pub use_method: UseMethod,
+    pub remaining: u64,
+    pub total: u64,
+}
+
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Debug, PartialEq, Eq)]
+pub enum CollectionDetails {
+    V1 { size: u64 },
+}
+
+/// Unlike `state::TokenStandard`, includes the programmable NFT standards, which this version of
+/// `mpl_token_metadata` predates.
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, Debug, PartialEq, Eq)]
+pub enum TokenStandard {
+    NonFungible,
+    FungibleAsset,
+    Fungible,
+    NonFungibleEdition,
+    ProgrammableNonFungible,
+    ProgrammableNonFungibleEdition,
+}
+
+/// Rule set configuration of programmable NFTs, which this version of `mpl_token_metadata`
+/// predates.
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Debug, PartialEq, Eq)]
+pub enum ProgrammableConfig {
+    V1 { rule_set: Option<Pubkey> },
+}
+
+impl From<DataV2> for state::DataV2 {
+    fn from(data: DataV2) -> Self {
+        state::DataV2 {
+            name: data.name,
+            symbol: data.symbol,
+            uri: data.uri,
+            seller_fee_basis_points: data.seller_fee_basis_points,
+            creators: data
+                .creators
+                .map(|creators| creators.into_iter().map(Into::into).collect()),
+            collection: data.collection.map(Into::into),
+            uses: data.uses.map(Into::into),
+        }
+    }
+}
+
+impl From<state::DataV2> for DataV2 {
+    fn from(data: state::DataV2) -> Self {
+        DataV2 {
+            name: data.name,
+            symbol: data.symbol,
+            uri: data.uri,
+            seller_fee_basis_points: data.seller_fee_basis_points,
+            creators: data
+                .creators
+                .map(|creators| creators.into_iter().map(Into::into).collect()),
+            collection: data.collection.map(Into::into),
+            uses: data.uses.map(Into::into),
+        }
+    }
+}
+
+impl From<Creator> for state::Creator {
+    fn from(creator: Creator) -> Self {
+        state::Creator {
+            address: creator.address,
+            verified: creator.verified,
+            share: creator.share,
+        }
+    }
+}
+
+impl From<state::Creator> for Creator {
+    fn from(creator: state::Creator) -> Self {
+        Creator {
+            address: creator.address,
+            verified: creator.verified,
+            share: creator.share,
+        }
+    }
+}
+
+impl From<Collection> for state::Collection {
+    fn from(collection: Collection) -> Self {
+        state::Collection {
+            verified: collection.verified,
+            key: collection.key,
+        }
+    }
+}
+
+impl From<state::Collection> for Collection {
+    fn from(collection: state::Collection) -> Self {
+        Collection {
+            verified: collection.verified,
+            key: collection.key,
+        }
+    }
+}
+
+impl From<UseMethod> for state::UseMethod {
+    fn from(use_method: UseMethod) -> Self {
+        match use_method {
+            UseMethod::Burn => state::UseMethod::Burn,
+            UseMethod::Multiple => state::UseMethod::Multiple,
+            UseMethod::Single => state::UseMethod::Single,
+        }
+    }
+}
+
+impl From<state::UseMethod> for UseMethod {
+    fn from(use_method: state::UseMethod) -> Self {
+        match use_method {
+            state::UseMethod::Burn => UseMethod::Burn,
+            state::UseMethod::Multiple => UseMethod::Multiple,
+            state::UseMethod::Single => UseMethod::Single,
+        }
+    }
+}
+
+impl From<Uses> for state::Uses {
+    fn from(uses: Uses) -> Self {
+        state::Uses {
+            use_method: uses.use_method.into(),
+            remaining: uses.remaining,
+            total: uses.total,
+        }
+    }
+}
+
+impl From<state::Uses> for Uses {
+    fn from(uses: state::Uses) -> Self {
+        Uses {
+            use_method: uses.use_method.into(),
+            remaining: uses.remaining,
+            total: uses.total,
+        }
+    }
+}
+
+impl From<CollectionDetails> for state::CollectionDetails {
+    fn from(details: CollectionDetails) -> Self {
+        match details {
+            CollectionDetails::V1 { size } => state::CollectionDetails::V1 { size },
+        }
+    }
+}
+
+impl From<state::CollectionDetails> for CollectionDetails {
+    fn from(details: state::CollectionDetails) -> Self {
+        match details {
+            state::CollectionDetails::V1 { size } => CollectionDetails::V1 { size },
+        }
+    }
+}
+
+impl From<state::TokenStandard> for TokenStandard {
+    fn from(token_standard: state::TokenStandard) -> Self {
+        match token_standard {
+            state::TokenStandard::NonFungible => TokenStandard::NonFungible,
+            state::TokenStandard::FungibleAsset => TokenStandard::FungibleAsset,
+            state::TokenStandard::Fungible => TokenStandard::Fungible,
+            state::TokenStandard::NonFungibleEdition => TokenStandard::NonFungibleEdition,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn idl_types_define_every_mirror() {
+        let types: Vec<serde_json::Value> = serde_json::from_str(IDL_TYPES).unwrap();
+        let names: Vec<_> = types
+            .iter()
+            .map(|ty| ty["name"].as_str().unwrap())
+            .collect();
+        assert_eq!(
+            names,
+            [
+                "DataV2",
+                "Creator",
+                "Collection",
+                "UseMethod",
+                "Uses",
+                "CollectionDetails",
+                "TokenStandard",
+                "ProgrammableConfig",
+            ]
+        );
+    }
+}
@@ -0,0 +1,143 @@
+use anchor_lang::prelude::*;
+use mpl_token_metadata::pda::{find_program_as_burner_account, find_use_authority_account};
+use mpl_token_metadata::state::{Metadata, TokenMetadataAccount, UseAuthorityRecord, UseMethod};
+
+use crate::error::MplCpiError;
+use crate::{ApproveUseAuthority, RevokeUseAuthority, Utilize};
+
+/// Use authority record PDA of `user` for the NFT minted from `mint`.
+pub fn find_use_authority_record(mint: &Pubkey, user: &Pubkey) -> (Pubkey, u8) {
+    find_use_authority_account(mint, user)
+}
+
+/// The metadata program's burner PDA, required when delegating or consuming delegated uses.
+pub fn find_burner() -> (Pubkey, u8) {
+    find_program_as_burner_account()
+}
+
+/// Number of uses `user` may still consume through its use authority record.
+///
+/// Returns zero when the record has not been created.
+pub fn allowed_uses(
+    use_authority_record: &AccountInfo,
+    mint: &Pubkey,
+    user: &Pubkey,
+) -> Result<u64> {
+    assert_use_authority_record(use_authority_record, mint, user)?;
+    if use_authority_record.data_is_empty() {
+        return Ok(0);
+    }
+    Ok(UseAuthorityRecord::from_account_info(use_authority_record)?.allowed_uses)
+}
+
+/// Delegates `number_of_uses` uses of the NFT to `user`.
+pub fn delegate_uses<'a, 'b, 'c, 'info>(
+    ctx: CpiContext<'a, 'b, 'c, 'info, ApproveUseAuthority<'info>>,
+    number_of_uses: u64,
+) -> Result<()> {
+    assert_use_authority_record(
+        &ctx.accounts.use_authority_record,
+        &ctx.accounts.mint.key(),
+        &ctx.accounts.user.key(),
+    )?;
+    assert_burner(&ctx.accounts.burner)?;
+    crate::approve_use_authority(ctx, number_of_uses)
+}
+
+/// Revokes every use delegated to `user`.
+pub fn revoke_uses<'a, 'b, 'c, 'info>(
+    ctx: CpiContext<'a, 'b, 'c, 'info, RevokeUseAuthority<'info>>,
+) -> Result<()> {
+    assert_use_authority_record(
+        &ctx.accounts.use_authority_record,
+        &ctx.accounts.mint.key(),
+        &ctx.accounts.user.key(),
+    )?;
+    crate::revoke_use_authority(ctx)
+}
+
+/// Consumes `number_of_uses` uses of the NFT, either as its owner or as a delegated use authority.
+///
+/// For delegated use authorities the record and burner are checked, and the CPI is skipped
+/// with [`MplCpiError::InsufficientDelegatedUses`] if the record does not allow enough uses.
+pub fn consume_uses<'a, 'b, 'c, 'info>(
+    ctx: CpiContext<'a, 'b, 'c, 'info, Utilize<'info>>,
+    number_of_uses: u64,
+) -> Result<()> {
+    if ctx.accounts.use_authority.key() != ctx.accounts.owner.key() {
+        let allowed = allowed_uses(
+            &ctx.accounts.use_authority_record_pda,
+            &ctx.accounts.mint.key(),
+            &ctx.accounts.use_authority.key(),
+        )?;
+        if allowed < number_of_uses {
+            return err!(MplCpiError::InsufficientDelegatedUses);
+        }
+        assert_burner(&ctx.accounts.burner)?;
+    }
+    crate::utilize(ctx, number_of_uses)
+}
+
+/// What a call to `utilize` will do to an item, as predicted by [`check_uses`].
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct UseOutcome {
+    pub use_method: UseMethod,
+    /// Uses left on the metadata after the call.
+    pub remaining: u64,
+    /// Whether the metadata program will burn the token as part of the call.
+    pub will_burn: bool,
+}
+
+/// Checks that `number_of_uses` can be consumed from the item's `Metadata.uses` without the CPI
+/// failing, and reports whether the token will be burned.
+pub fn check_uses(metadata: &AccountInfo, number_of_uses: u64) -> Result<UseOutcome> {
+    let uses = match Metadata::from_account_info(metadata)?.uses {
+        Some(uses) => uses,
+        None => return err!(MplCpiError::Unusable),
+    };
+    if uses.use_method == UseMethod::Single && number_of_uses != 1 {
+        return err!(MplCpiError::InvalidNumberOfUses);
+    }
+    if number_of_uses > uses.total {
+        return err!(MplCpiError::NotEnoughUses);
+    }
+    let remaining = uses
+        .remaining
+        .checked_sub(number_of_uses)
+        .ok_or(MplCpiError::NotEnoughUses)?;
+    Ok(UseOutcome {
+        will_burn: uses.use_method == UseMethod::Burn && remaining == 0,
+        use_method: uses.use_method,
+        remaining,
+    })
+}
+
+/// [`consume_uses`] preceded by [`check_uses`], so insufficient uses fail before the CPI.
+pub fn utilize_checked<'a, 'b, 'c, 'info>(
+    ctx: CpiContext<'a, 'b, 'c, 'info, Utilize<'info>>,
+    number_of_uses: u64,
+) -> Result<UseOutcome> {
+    let outcome = check_uses(&ctx.accounts.metadata, number_of_uses)?;
+    consume_uses(ctx, number_of_uses)?;
+    Ok(outcome)
+}
+
+fn assert_use_authority_record(
+    use_authority_record: &AccountInfo,
+    mint: &Pubkey,
+    user: &Pubkey,
+) -> Result<()> {
+    let (expected, _) = find_use_authority_record(mint, user);
+    if use_authority_record.key() != expected {
+        return err!(MplCpiError::InvalidUseAuthorityRecord);
+    }
+    Ok(())
+}
+
+fn assert_burner(burner: &AccountInfo) -> Result<()> {
+    let (expected, _) = find_burner();
+    if burner.key() != expected {
+        return err!(MplCpiError::InvalidBurner);
+    }
+    Ok(())
+}
@@ -0,0 +1,358 @@
+//! Runs the metadata, token and system programs natively so the CPI wrappers can be tested
+//! without a validator.
+//!
+//! `invoke_signed` is routed to the program's processor with the accounts it was given, checking
+//! signer and writable privileges the way the runtime does. The system program is emulated for
+//! the instructions the metadata program uses. Accounts are leaked so they live for `'static`.
+//!
+//! Like the runtime, every invocation sees the whole data of its accounts: programs serialize
+//! through `&mut [u8]`, which leaves the slice of the `AccountInfo` they wrote to shortened.
+#![allow(dead_code)]
+
+use std::cell::RefCell;
+use std::collections::HashMap;
+use std::rc::Rc;
+use std::sync::Once;
+
+use anchor_lang::prelude::*;
+use anchor_lang::solana_program::entrypoint::{ProgramResult, SUCCESS};
+use anchor_lang::solana_program::program_pack::Pack;
+use anchor_lang::solana_program::program_stubs::{self, SyscallStubs};
+use anchor_lang::solana_program::program_utils::limited_deserialize;
+use anchor_lang::solana_program::system_instruction::SystemInstruction;
+use anchor_lang::solana_program::sysvar;
+use anchor_lang::solana_program::{bpf_loader, instruction::Instruction, system_program};
+use anchor_spl::token::{spl_token, Token};
+use mpl_cpi::edition::find_edition_marker_account;
+use mpl_token_metadata::pda::{find_master_edition_account, find_metadata_account};
+use mpl_token_metadata::state::{MasterEditionV2, TokenMetadataAccount};
+
+/// Program the wrappers are called from.
+pub const CALLER: Pubkey = Pubkey::new_from_array([7; 32]);
+
+pub const SOL: u64 = 1_000_000_000;
+
+thread_local! {
+    static CALLERS: RefCell<Vec<Pubkey>> = RefCell::new(vec![CALLER]);
+    static BUFFERS: RefCell<HashMap<Pubkey, (*mut u8, usize)>> = RefCell::new(HashMap::new());
+}
+
+/// Leaks `data` as the buffer of account `key`.
+fn store(key: Pubkey, data: Vec<u8>) -> &'static mut [u8] {
+    let data = Box::leak(data.into_boxed_slice());
+    BUFFERS.with(|buffers| {
+        buffers
+            .borrow_mut()
+            .insert(key, (data.as_mut_ptr(), data.len()))
+    });
+    data
+}
+
+/// The whole buffer of account `key`.
+fn buffer(key: &Pubkey) -> &'static mut [u8] {
+    let (data, len) = BUFFERS.with(|buffers| buffers.borrow()[key]);
+    // The buffer is leaked and only ever accessed through the `RefCell`s of account infos, as
+    // on chain.
+    unsafe { std::slice::from_raw_parts_mut(data, len) }
+}
+
+struct Runtime;
+
+impl SyscallStubs for Runtime {
+    fn sol_invoke_signed(
+        &self,
+        instruction: &Instruction,
+        account_infos: &[AccountInfo],
+        signers_seeds: &[&[&[u8]]],
+    ) -> ProgramResult {
+        let caller = CALLERS.with(|callers| *callers.borrow().last().unwrap());
+        let signers = signers_seeds
+            .iter()
+            .map(|seeds| Pubkey::create_program_address(seeds, &caller))
+            .collect::<std::result::Result<Vec<_>, _>>()
+            .map_err(|_| ProgramError::InvalidSeeds)?;
+
+        let mut accounts = Vec::with_capacity(instruction.accounts.len());
+        for meta in &instruction.accounts {
+            let info = account_infos
+                .iter()
+                .find(|info| *info.key == meta.pubkey)
+                .ok_or(ProgramError::NotEnoughAccountKeys)?;
+            // Privileges are per account, not per position in the instruction.
+            let metas = instruction
+                .accounts
+                .iter()
+                .filter(|other| other.pubkey == meta.pubkey);
+            let is_signer = metas.clone().any(|meta| meta.is_signer);
+            let is_writable = metas.clone().any(|meta| meta.is_writable);
+            if is_signer && !info.is_signer && !signers.contains(info.key) {
+                return Err(ProgramError::MissingRequiredSignature);
+            }
+            if is_writable && !info.is_writable {
+                return Err(ProgramError::InvalidArgument);
+            }
+            let mut info = info.clone();
+            info.is_signer = is_signer;
+            info.is_writable = is_writable;
+            info.data = Rc::new(RefCell::new(buffer(info.key)));
+            accounts.push(info);
+        }
+        let accounts = &*Box::leak(accounts.into_boxed_slice());
+
+        CALLERS.with(|callers| callers.borrow_mut().push(instruction.program_id));
+        let result = process(&instruction.program_id, accounts, &instruction.data);
+        CALLERS.with(|callers| callers.borrow_mut().pop());
+
+        for info in account_infos {
+            let is_writable = instruction
+                .accounts
+                .iter()
+                .any(|meta| meta.pubkey == *info.key && meta.is_writable);
+            if is_writable {
+                *info.try_borrow_mut_data()? = buffer(info.key);
+            }
+        }
+        result
+    }
+
+    fn sol_get_rent_sysvar(&self, var_addr: *mut u8) -> u64 {
+        unsafe { *(var_addr as *mut Rent) = Rent::default() };
+        SUCCESS
+    }
+}
+
+fn process<'a>(program_id: &Pubkey, accounts: &'a [AccountInfo<'a>], data: &[u8]) -> ProgramResult {
+    if *program_id == mpl_token_metadata::ID {
+        mpl_token_metadata::processor::process_instruction(&mpl_token_metadata::ID, accounts, data)
+    } else if *program_id == spl_token::ID {
+        spl_token::processor::Processor::process(&spl_token::ID, accounts, data)
+    } else if *program_id == system_program::ID {
+        process_system(accounts, data)
+    } else {
+        Err(ProgramError::IncorrectProgramId)
+    }
+}
+
+fn process_system(accounts: &[AccountInfo], data: &[u8]) -> ProgramResult {
+    let instruction =
+        limited_deserialize(data, 1024).map_err(|_| ProgramError::InvalidInstructionData)?;
+    match instruction {
+        SystemInstruction::CreateAccount {
+            lamports,
+            space,
+            owner,
+        } => {
+            transfer(&accounts[0], &accounts[1], lamports)?;
+            allocate(&accounts[1], space)?;
+            assign(&accounts[1], &owner)
+        }
+        SystemInstruction::Transfer { lamports } => transfer(&accounts[0], &accounts[1], lamports),
+        SystemInstruction::Allocate { space } => allocate(&accounts[0], space),
+        SystemInstruction::Assign { owner } => assign(&accounts[0], &owner),
+        _ => Err(ProgramError::InvalidInstructionData),
+    }
+}
+
+fn transfer(from: &AccountInfo, to: &AccountInfo, lamports: u64) -> ProgramResult {
+    if !from.is_signer {
+        return Err(ProgramError::MissingRequiredSignature);
+    }
+    let remaining = from
+        .lamports()
+        .checked_sub(lamports)
+        .ok_or(ProgramError::InsufficientFunds)?;
+    **from.try_borrow_mut_lamports()? = remaining;
+    **to.try_borrow_mut_lamports()? += lamports;
+    Ok(())
+}
+
+fn allocate(account: &AccountInfo, space: u64) -> ProgramResult {
+    if !account.is_signer {
+        return Err(ProgramError::MissingRequiredSignature);
+    }
+    if *account.owner != system_program::ID || !account.data_is_empty() {
+        return Err(ProgramError::AccountAlreadyInitialized);
+    }
+    *account.try_borrow_mut_data()? = store(*account.key, vec![0; space as usize]);
+    Ok(())
+}
+
+fn assign(account: &AccountInfo, owner: &Pubkey) -> ProgramResult {
+    if !account.is_signer {
+        return Err(ProgramError::MissingRequiredSignature);
+    }
+    account.assign(owner);
+    Ok(())
+}
+
+/// Routes cross-program invocations of this test binary to the native processors.
+pub fn setup() {
+    static SETUP: Once = Once::new();
+    SETUP.call_once(|| {
+        program_stubs::set_syscall_stubs(Box::new(Runtime));
+    });
+}
+
+pub fn account(
+    key: Pubkey,
+    is_signer: bool,
+    is_writable: bool,
+    lamports: u64,
+    data: Vec<u8>,
+    owner: Pubkey,
+) -> AccountInfo<'static> {
+    AccountInfo::new(
+        Box::leak(Box::new(key)),
+        is_signer,
+        is_writable,
+        Box::leak(Box::new(lamports)),
+        store(key, data),
+        Box::leak(Box::new(owner)),
+        false,
+        0,
+    )
+}
+
+pub fn program(id: Pubkey) -> AccountInfo<'static> {
+    let mut info = account(id, false, false, 1, vec![], bpf_loader::ID);
+    info.executable = true;
+    info
+}
+
+pub fn rent() -> AccountInfo<'static> {
+    let data = vec![0; Rent::size_of()];
+    let mut info = account(sysvar::rent::ID, false, false, 1, data, sysvar::ID);
+    Rent::default().to_account_info(&mut info).unwrap();
+    info
+}
+
+/// Funded system account that signs.
+pub fn wallet() -> AccountInfo<'static> {
+    account(
+        Pubkey::new_unique(),
+        true,
+        true,
+        100 * SOL,
+        vec![],
+        system_program::ID,
+    )
+}
+
+/// Account that does not exist yet.
+pub fn empty(key: Pubkey) -> AccountInfo<'static> {
+    account(key, false, true, 0, vec![], system_program::ID)
+}
+
+pub fn mint(authority: &Pubkey, supply: u64) -> AccountInfo<'static> {
+    let mut data = vec![0; spl_token::state::Mint::LEN];
+    spl_token::state::Mint {
+        mint_authority: Some(*authority).into(),
+        supply,
+        decimals: 0,
+        is_initialized: true,
+        freeze_authority: Some(*authority).into(),
+    }
+    .pack_into_slice(&mut data);
+    account(Pubkey::new_unique(), false, true, SOL, data, spl_token::ID)
+}
+
+pub fn token_account(mint: &Pubkey, owner: &Pubkey, amount: u64) -> AccountInfo<'static> {
+    let mut data = vec![0; spl_token::state::Account::LEN];
+    spl_token::state::Account {
+        mint: *mint,
+        owner: *owner,
+        amount,
+        state: spl_token::state::AccountState::Initialized,
+        ..Default::default()
+    }
+    .pack_into_slice(&mut data);
+    account(Pubkey::new_unique(), false, true, SOL, data, spl_token::ID)
+}
+
+/// Returns `info` marked as a signer, as when its keypair signs the transaction.
+pub fn signer(mut info: AccountInfo<'static>) -> AccountInfo<'static> {
+    info.is_signer = true;
+    info
+}
+
+/// Master edition NFT held by its update authority.
+pub struct Nft {
+    pub payer: AccountInfo<'static>,
+    pub authority: AccountInfo<'static>,
+    pub mint: AccountInfo<'static>,
+    pub token: AccountInfo<'static>,
+    pub metadata: AccountInfo<'static>,
+    pub edition: AccountInfo<'static>,
+    pub edition_marker: AccountInfo<'static>,
+}
+
+/// Creates the metadata and master edition of a new NFT through the crate's wrappers.
+pub fn create_master_edition(max_supply: Option<u64>) -> Nft {
+    setup();
+    let payer = wallet();
+    let authority = wallet();
+    let mint = mint(authority.key, 1);
+    let token = token_account(mint.key, authority.key, 1);
+    let metadata = empty(find_metadata_account(mint.key).0);
+    let edition = empty(find_master_edition_account(mint.key).0);
+    let edition_marker = empty(find_edition_marker_account(mint.key, 1).0);
+
+    mpl_cpi::create_metadata_accounts_v3(
+        CpiContext::new(
+            program(mpl_token_metadata::ID),
+            mpl_cpi::CreateMetadataAccountV3 {
+                metadata_account: metadata.clone(),
+                mint: mint.clone(),
+                mint_authority: authority.clone(),
+                payer: payer.clone(),
+                update_authority: authority.clone(),
+                system_program: Program::try_from(&program(system_program::ID)).unwrap(),
+                rent: Sysvar::from_account_info(&rent()).unwrap(),
+            },
+        ),
+        "Master".to_string(),
+        "MSTR".to_string(),
+        "https://example.com/master.json".to_string(),
+        0,
+        true,
+        true,
+        None,
+        None,
+        None,
+        None,
+    )
+    .unwrap();
+
+    mpl_cpi::create_master_edition_v3(
+        CpiContext::new(
+            program(mpl_token_metadata::ID),
+            mpl_cpi::CreateMasterEditionV3 {
+                edition: edition.clone(),
+                mint: mint.clone(),
+                update_authority: authority.clone(),
+                mint_authority: authority.clone(),
+                metadata: metadata.clone(),
+                payer: payer.clone(),
+                system_program: Program::try_from(&program(system_program::ID)).unwrap(),
+                token_program: Program::<Token>::try_from(&program(spl_token::ID)).unwrap(),
+                rent: Sysvar::from_account_info(&rent()).unwrap(),
+            },
+        ),
+        max_supply,
+    )
+    .unwrap();
+
+    Nft {
+        payer,
+        authority,
+        mint,
+        token,
+        metadata,
+        edition,
+        edition_marker,
+    }
+}
+
+pub fn master_edition(nft: &Nft) -> MasterEditionV2 {
+    MasterEditionV2::from_account_info(&nft.edition).unwrap()
+}
@@ -0,0 +1,94 @@
+#![allow(clippy::result_large_err)]
+
+mod common;
+
+use anchor_lang::prelude::*;
+use anchor_lang::solana_program::system_program;
+use anchor_spl::token::spl_token;
+use mpl_cpi::edition::is_edition_minted;
+use mpl_token_metadata::error::MetadataError;
+use mpl_token_metadata::pda::{find_master_edition_account, find_metadata_account};
+
+use common::*;
+
+fn print(nft: &Nft, edition: u64) -> Result<()> {
+    let new_mint_authority = wallet();
+    let new_mint = mint(new_mint_authority.key, 1);
+    mpl_cpi::mint_new_edition_from_master_edition_via_token(
+        CpiContext::new(
+            program(mpl_token_metadata::ID),
+            mpl_cpi::MintNewEditionFromMasterEditionViaToken {
+                new_metadata: empty(find_metadata_account(new_mint.key).0),
+                new_edition: empty(find_master_edition_account(new_mint.key).0),
+                master_edition: nft.edition.clone(),
+                new_mint,
+                edition_mark_pda: nft.edition_marker.clone(),
+                new_mint_authority,
+                payer: nft.payer.clone(),
+                token_account_owner: nft.authority.clone(),
+                token_account: nft.token.clone(),
+                new_metadata_update_authority: nft.authority.clone(),
+                metadata: nft.metadata.clone(),
+                metadata_mint: nft.mint.clone(),
+                system_program: Program::try_from(&program(system_program::ID)).unwrap(),
+                token_program: Program::try_from(&program(spl_token::ID)).unwrap(),
+                rent: Sysvar::from_account_info(&rent()).unwrap(),
+            },
+        ),
+        edition,
+    )
+}
+
+#[test]
+fn capped_master_edition_stops_printing_at_max_supply() {
+    let nft = create_master_edition(Some(2));
+    assert_eq!(master_edition(&nft).max_supply, Some(2));
+    assert_eq!(master_edition(&nft).supply, 0);
+
+    print(&nft, 1).unwrap();
+    print(&nft, 2).unwrap();
+    assert_eq!(master_edition(&nft).supply, 2);
+    assert!(is_edition_minted(&nft.edition_marker, nft.mint.key, 2).unwrap());
+
+    let err = print(&nft, 3).unwrap_err();
+    assert_eq!(
+        ProgramError::from(err),
+        MetadataError::EditionNumberGreaterThanMaxSupply.into()
+    );
+    assert_eq!(master_edition(&nft).supply, 2);
+}
+
+#[test]
+fn unlimited_master_edition_keeps_printing() {
+    let nft = create_master_edition(None);
+    assert_eq!(master_edition(&nft).max_supply, None);
+
+    for edition in 1..=5 {
+        print(&nft, edition).unwrap();
+    }
+    assert_eq!(master_edition(&nft).supply, 5);
+    assert!(is_edition_minted(&nft.edition_marker, nft.mint.key, 5).unwrap());
+    assert!(!is_edition_minted(&nft.edition_marker, nft.mint.key, 6).unwrap());
+}
+
+#[test]
+fn printing_an_edition_twice_fails() {
+    let nft = create_master_edition(None);
+    print(&nft, 1).unwrap();
+
+    let err = print(&nft, 1).unwrap_err();
+    assert_eq!(
+        ProgramError::from(err),
+        MetadataError::AlreadyInitialized.into()
+    );
+}
+
+#[test]
+fn print_rejects_marker_of_another_edition() {
+    let nft = create_master_edition(None);
+    let err = print(&nft, mpl_cpi::EDITION_MARKER_BIT_SIZE).unwrap_err();
+    assert_eq!(
+        ProgramError::from(err),
+        ProgramError::from(error!(mpl_cpi::error::MplCpiError::InvalidEditionMarker))
+    );
+}
@@ -0,0 +1,157 @@
+#![allow(clippy::result_large_err)]
+
+mod common;
+
+use anchor_lang::prelude::*;
+use anchor_lang::solana_program::system_program;
+use anchor_spl::token::spl_token;
+use mpl_cpi::edition::is_edition_minted;
+use mpl_token_metadata::pda::{find_master_edition_account, find_metadata_account};
+use mpl_token_metadata::state::{Edition, TokenMetadataAccount};
+use mpl_token_vault::error::VaultError;
+use mpl_token_vault::state::{
+    Key as VaultKey, SafetyDepositBox, Vault, VaultState, MAX_SAFETY_DEPOSIT_SIZE, MAX_VAULT_SIZE,
+    PREFIX as VAULT_PREFIX,
+};
+
+use common::*;
+
+/// Vault whose safety deposit box stores the token of a master edition.
+struct VaultFixture {
+    authority: AccountInfo<'static>,
+    vault: AccountInfo<'static>,
+    safety_deposit_box: AccountInfo<'static>,
+    store: AccountInfo<'static>,
+}
+
+fn deposit(nft: &Nft, state: VaultState) -> VaultFixture {
+    let authority = wallet();
+    let vault_key = Pubkey::new_unique();
+    let (vault_pda, _) = Pubkey::find_program_address(
+        &[
+            VAULT_PREFIX.as_bytes(),
+            mpl_token_vault::ID.as_ref(),
+            vault_key.as_ref(),
+        ],
+        &mpl_token_vault::ID,
+    );
+    let store = token_account(nft.mint.key, &vault_pda, 1);
+
+    let mut vault_data = Vault {
+        key: VaultKey::VaultV1,
+        token_program: spl_token::ID,
+        fraction_mint: Pubkey::new_unique(),
+        authority: *authority.key,
+        fraction_treasury: Pubkey::new_unique(),
+        redeem_treasury: Pubkey::new_unique(),
+        allow_further_share_creation: false,
+        pricing_lookup_address: Pubkey::new_unique(),
+        token_type_count: 1,
+        state,
+        locked_price_per_share: 0,
+    }
+    .try_to_vec()
+    .unwrap();
+    vault_data.resize(MAX_VAULT_SIZE, 0);
+    let vault = account(vault_key, false, true, SOL, vault_data, mpl_token_vault::ID);
+
+    let mut box_data = SafetyDepositBox {
+        key: VaultKey::SafetyDepositBoxV1,
+        vault: vault_key,
+        token_mint: *nft.mint.key,
+        store: *store.key,
+        order: 0,
+    }
+    .try_to_vec()
+    .unwrap();
+    box_data.resize(MAX_SAFETY_DEPOSIT_SIZE, 0);
+    let safety_deposit_box = account(
+        Pubkey::new_unique(),
+        false,
+        false,
+        SOL,
+        box_data,
+        mpl_token_vault::ID,
+    );
+
+    VaultFixture {
+        authority,
+        vault,
+        safety_deposit_box,
+        store,
+    }
+}
+
+/// Prints `edition` through the vault proxy and returns the new edition account.
+fn print(nft: &Nft, vault: &VaultFixture, edition: u64) -> Result<AccountInfo<'static>> {
+    let new_mint_authority = wallet();
+    let new_mint = mint(new_mint_authority.key, 1);
+    let new_edition = empty(find_master_edition_account(new_mint.key).0);
+    mpl_cpi::mint_edition_from_master_edition_via_vault_proxy(
+        CpiContext::new(
+            program(mpl_token_metadata::ID),
+            mpl_cpi::MintEditionFromMasterEditionViaVaultProxy {
+                new_metadata: empty(find_metadata_account(new_mint.key).0),
+                new_edition: new_edition.clone(),
+                master_edition: nft.edition.clone(),
+                new_mint,
+                edition_mark_pda: nft.edition_marker.clone(),
+                new_mint_authority,
+                payer: nft.payer.clone(),
+                vault_authority: vault.authority.clone(),
+                safety_deposit_store: vault.store.clone(),
+                safety_deposit_box: vault.safety_deposit_box.clone(),
+                vault: vault.vault.clone(),
+                new_metadata_update_authority: nft.authority.clone(),
+                metadata: nft.metadata.clone(),
+                token_program: Program::try_from(&program(spl_token::ID)).unwrap(),
+                token_vault_program: Program::try_from(&program(mpl_token_vault::ID)).unwrap(),
+                system_program: Program::try_from(&program(system_program::ID)).unwrap(),
+                rent: Sysvar::from_account_info(&rent()).unwrap(),
+            },
+        ),
+        edition,
+    )?;
+    Ok(new_edition)
+}
+
+#[test]
+fn prints_edition_held_by_vault() {
+    let nft = create_master_edition(Some(10));
+    let vault = deposit(&nft, VaultState::Combined);
+
+    let new_edition = print(&nft, &vault, 1).unwrap();
+    let new_edition = Edition::from_account_info(&new_edition).unwrap();
+    assert_eq!(new_edition.parent, *nft.edition.key);
+    assert_eq!(new_edition.edition, 1);
+    assert_eq!(master_edition(&nft).supply, 1);
+    assert!(is_edition_minted(&nft.edition_marker, nft.mint.key, 1).unwrap());
+
+    print(&nft, &vault, 2).unwrap();
+    assert_eq!(master_edition(&nft).supply, 2);
+}
+
+#[test]
+fn rejects_marker_of_another_edition() {
+    let nft = create_master_edition(None);
+    let vault = deposit(&nft, VaultState::Combined);
+
+    let err = print(&nft, &vault, mpl_cpi::EDITION_MARKER_BIT_SIZE).unwrap_err();
+    assert_eq!(
+        ProgramError::from(err),
+        ProgramError::from(error!(mpl_cpi::error::MplCpiError::InvalidEditionMarker))
+    );
+    assert_eq!(master_edition(&nft).supply, 0);
+}
+
+#[test]
+fn rejects_vault_that_is_not_combined() {
+    let nft = create_master_edition(None);
+    let vault = deposit(&nft, VaultState::Active);
+
+    let err = print(&nft, &vault, 1).unwrap_err();
+    assert_eq!(
+        ProgramError::from(err),
+        VaultError::VaultShouldBeCombined.into()
+    );
+}